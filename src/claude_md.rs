@@ -0,0 +1,112 @@
+//! Managed block of promoted memories inside CLAUDE.md
+//!
+//! Engram owns only the text between its markers, so the block can be
+//! regenerated any number of times without touching the rest of the file.
+
+use std::io::Write;
+use std::path::Path;
+
+pub const BEGIN_MARKER: &str = "<!-- engram:begin -->";
+pub const END_MARKER: &str = "<!-- engram:end -->";
+
+/// Render the managed block (markers included) for the given memory contents
+pub fn render_block<'a>(contents: impl IntoIterator<Item = &'a str>) -> String {
+    let mut block = format!("{}\n", BEGIN_MARKER);
+    for content in contents {
+        // Keep each memory on one bullet line, and escape comment openers so
+        // content can't fake a marker and cut the block short on the next sync
+        let line = content.replace('\n', " ").replace("<!--", "&lt;!--");
+        block.push_str(&format!("- {}\n", line));
+    }
+    block.push_str(END_MARKER);
+    block
+}
+
+/// Replace the managed block in `doc`, or append it if there isn't one yet
+pub fn replace_block(doc: &str, block: &str) -> String {
+    if let Some(start) = doc.find(BEGIN_MARKER) {
+        if let Some(end) = doc[start..].find(END_MARKER) {
+            let end = start + end + END_MARKER.len();
+            return format!("{}{}{}", &doc[..start], block, &doc[end..]);
+        }
+    }
+
+    let mut out = doc.to_string();
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    out.push_str(block);
+    out.push('\n');
+    out
+}
+
+/// Replace `path` with `contents` via a sibling temp file and a rename, so
+/// a crash or full disk mid-write leaves the old file whole
+pub fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+    let written = std::fs::File::create_new(&temp).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        if let Ok(existing) = std::fs::metadata(path) {
+            file.set_permissions(existing.permissions())?;
+        }
+        file.sync_all()
+    });
+    let result = written.and_then(|_| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_block_is_idempotent() {
+        let doc = "# Project\n\n## Learned Facts\n";
+        let block = render_block(["Use nextest", "CI runs on\npush"]);
+
+        let once = replace_block(doc, &block);
+        assert_eq!(
+            once,
+            "# Project\n\n## Learned Facts\n\n<!-- engram:begin -->\n- Use nextest\n- CI runs on push\n<!-- engram:end -->\n"
+        );
+        assert_eq!(replace_block(&once, &block), once);
+
+        // A new block replaces the old one and leaves surrounding text alone
+        let edited = format!("{}\nHand-written notes\n", once);
+        let updated = replace_block(&edited, &render_block(["Only this"]));
+        assert!(updated.contains("- Only this\n"));
+        assert!(!updated.contains("Use nextest"));
+        assert!(updated.starts_with("# Project\n"));
+        assert!(updated.ends_with("\nHand-written notes\n"));
+    }
+
+    #[test]
+    fn test_markers_in_content_are_escaped() {
+        let doc = replace_block("# Notes\n", &render_block(["Ends with <!-- engram:end --> here", "Second"]));
+        assert!(doc.contains("- Ends with &lt;!-- engram:end --> here\n"));
+
+        // The next sync replaces the whole block, leaving nothing stale behind
+        let resynced = replace_block(&doc, &render_block(["Only this"]));
+        assert_eq!(resynced, "# Notes\n\n<!-- engram:begin -->\n- Only this\n<!-- engram:end -->\n");
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CLAUDE.md");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_replace_block_in_empty_doc() {
+        assert_eq!(replace_block("", &render_block([])), "<!-- engram:begin -->\n<!-- engram:end -->\n");
+    }
+}
@@ -0,0 +1,84 @@
+//! Heuristic detection of what a memory's content looks like
+//!
+//! Used only as a display hint, so it favours being cheap over being right:
+//! anything that isn't clearly a URL, shell command or code is prose.
+
+/// Commands that mark a single line as a shell invocation
+const SHELL_COMMANDS: [&str; 24] = [
+    "cargo", "git", "npm", "npx", "yarn", "pnpm", "pip", "python", "python3", "docker", "kubectl",
+    "make", "cd", "ls", "grep", "rg", "find", "curl", "wget", "sudo", "brew", "apt", "export", "ssh",
+];
+
+/// Share of code punctuation above which multi-line text reads as code
+const CODE_SYMBOL_RATIO: f64 = 0.08;
+
+/// Classify content as "url", "shell", "code" or "prose"
+pub fn detect_content_kind(content: &str) -> &'static str {
+    let trimmed = content.trim();
+
+    if !trimmed.contains(char::is_whitespace)
+        && (trimmed.starts_with("http://") || trimmed.starts_with("https://"))
+    {
+        return "url";
+    }
+
+    if trimmed.contains("```") {
+        return "code";
+    }
+
+    if !trimmed.contains('\n') && looks_like_shell(trimmed) {
+        return "shell";
+    }
+
+    if trimmed.lines().count() > 1 && looks_like_code(trimmed) {
+        return "code";
+    }
+
+    "prose"
+}
+
+fn looks_like_shell(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix("$ ") {
+        return !rest.is_empty();
+    }
+    let first = line.split_whitespace().next().unwrap_or("");
+    // "git rebase is handy." is a sentence, not a command
+    SHELL_COMMANDS.contains(&first) && !line.ends_with('.')
+}
+
+fn looks_like_code(text: &str) -> bool {
+    let statement_lines = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| l.ends_with(';') || l.ends_with('{') || l.ends_with('}'))
+        .count();
+    if statement_lines >= 2 {
+        return true;
+    }
+
+    let symbols = text.chars().filter(|c| "{}()[];=<>".contains(*c)).count();
+    let visible = text.chars().filter(|c| !c.is_whitespace()).count();
+    visible > 0 && symbols as f64 / visible as f64 > CODE_SYMBOL_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_content_kinds() {
+        assert_eq!(detect_content_kind("https://docs.rs/rusqlite"), "url");
+        assert_eq!(detect_content_kind("cargo test --workspace"), "shell");
+        assert_eq!(detect_content_kind("$ make release"), "shell");
+        assert_eq!(detect_content_kind("fn main() {\n    run();\n}"), "code");
+        assert_eq!(detect_content_kind("Use this:\n```\nlet x = 1;\n```"), "code");
+    }
+
+    #[test]
+    fn test_prose_is_the_default() {
+        assert_eq!(detect_content_kind("Auth uses JWT with 15min expiry"), "prose");
+        assert_eq!(detect_content_kind("git rebase is preferred over merge here."), "prose");
+        assert_eq!(detect_content_kind("See https://example.com for details"), "prose");
+        assert_eq!(detect_content_kind("First line\nsecond line of notes"), "prose");
+    }
+}
@@ -1,44 +1,197 @@
-use rusqlite::{Connection, Result, params};
+use rusqlite::{Connection, OpenFlags, Result, Transaction, TransactionBehavior, params};
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Type alias for memory identifiers
 pub type MemoryId = String;
 
+/// Default window for `hot`, in hours
+pub const DEFAULT_HOT_HOURS: u32 = 24;
+
+/// Profile that keeps using the legacy `.engram/engram.db` store
+pub const DEFAULT_PROFILE: &str = "global";
+
+/// Directory holding named profiles, under the platform's data directory:
+/// `$XDG_DATA_HOME/engram/profiles` on Linux, `~/Library/Application
+/// Support/engram/profiles` on macOS, `%APPDATA%\engram\profiles` on Windows.
+pub fn profiles_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("engram").join("profiles"))
+}
+
+/// Move profiles left in the old `~/.engram/profiles` to `profiles_dir`,
+/// unless that already exists. Returns (from, to) when something moved.
+pub fn migrate_legacy_profiles() -> std::io::Result<Option<(PathBuf, PathBuf)>> {
+    let (Some(home), Some(dir)) = (dirs::home_dir(), profiles_dir()) else {
+        return Ok(None);
+    };
+    let legacy = home.join(".engram").join("profiles");
+    Ok(migrate_profiles(&legacy, &dir)?.then_some((legacy, dir)))
+}
+
+/// Move the `legacy` profiles directory to `dir` unless `dir` already
+/// exists. Returns whether it moved; a failed move (e.g. across
+/// filesystems) is an error naming both directories.
+fn migrate_profiles(legacy: &Path, dir: &Path) -> std::io::Result<bool> {
+    if !legacy.is_dir() || dir.exists() {
+        return Ok(false);
+    }
+    let moved = match dir.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::rename(legacy, dir)),
+        None => fs::rename(legacy, dir),
+    };
+    moved.map(|_| true).map_err(|e| {
+        std::io::Error::new(e.kind(), format!("can't move {} to {}: {}", legacy.display(), dir.display(), e))
+    })
+}
+
+/// Whether `name` can be used as a profile directory name
+pub fn valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 /// Configuration for engram database
 #[derive(Debug, Clone)]
 pub struct Config {
+    /// Database file; `:memory:` gives an in-memory store that is not persisted
     pub db_path: PathBuf,
+    /// Extra SQLite pragmas applied after opening, as (name, value)
+    pub pragmas: Vec<(String, String)>,
+    /// Default window for `hot` when no --hours is given
+    pub hot_hours: u32,
+    /// Taps of the same memory within this many seconds count once (0 = off)
+    pub tap_dedup_secs: u32,
+    /// Which events get written to the log
+    pub event_policy: EventPolicy,
+    /// Shell command that reads text on stdin and prints a JSON array of
+    /// floats, used by semantic search (`semantic` feature)
+    pub embed_command: Option<String>,
+    /// CLAUDE.md whose managed block is rewritten on every promote
+    pub claude_md_path: Option<PathBuf>,
+    /// Shell command run for each promoted memory with ID, content and taps as $1-$3
+    pub on_promote: Option<String>,
+    /// Shell command run for each forgotten memory, same arguments as `on_promote`
+    pub on_forget: Option<String>,
+    /// When set, taps lose half their weight every this many days without a
+    /// new tap; see `engram::effective_score`
+    pub decay_half_life_days: Option<f64>,
+    /// Edits count as engagement: they reset last_tapped_at, so a memory
+    /// that is being maintained doesn't look stale
+    pub edit_refreshes: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            db_path: PathBuf::from(".engram").join("engram.db"),
+            pragmas: Vec::new(),
+            hot_hours: DEFAULT_HOT_HOURS,
+            tap_dedup_secs: 0,
+            event_policy: EventPolicy::default(),
+            embed_command: None,
+            claude_md_path: None,
+            on_promote: None,
+            on_forget: None,
+            decay_half_life_days: None,
+            edit_refreshes: false,
+        }
+    }
 }
 
 impl Config {
     /// Create config from environment variables
     pub fn from_env() -> Self {
-        let db_path = if let Ok(path) = std::env::var("ENGRAM_DB_PATH") {
-            PathBuf::from(path)
+        Self::from_env_for_profile(&std::env::var("ENGRAM_PROFILE").unwrap_or_default())
+    }
+
+    /// Like `from_env`, but for `profile` instead of ENGRAM_PROFILE
+    pub fn from_env_for_profile(profile: &str) -> Self {
+        let mut config = Config::default();
+
+        let profile = Some(profile).filter(|p| *p != DEFAULT_PROFILE && !p.is_empty());
+        if let Ok(path) = std::env::var("ENGRAM_DB_PATH") {
+            config.db_path = PathBuf::from(path);
+        } else if let Some(profile) = profile {
+            // Named profile: <data dir>/engram/profiles/<name>/engram.db
+            let dir = profiles_dir().expect("No data directory for profiles").join(profile);
+            fs::create_dir_all(&dir).expect("Failed to create profile directory");
+            config.db_path = dir.join("engram.db");
         } else {
             // Default: .engram/engram.db in current directory
-            let data_dir = PathBuf::from(".engram");
-            fs::create_dir_all(&data_dir).expect("Failed to create .engram directory");
-            data_dir.join("engram.db")
-        };
-
-        Config { db_path }
+            fs::create_dir_all(".engram").expect("Failed to create .engram directory");
+        }
+
+        // Comma-separated name=value pairs, e.g. "cache_size=-20000,synchronous=NORMAL"
+        if let Ok(pragmas) = std::env::var("ENGRAM_DB_PRAGMAS") {
+            config.pragmas = pragmas
+                .split(',')
+                .filter_map(|p| p.split_once('='))
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .collect();
+        }
+
+        if let Some(hours) = std::env::var("ENGRAM_HOT_HOURS").ok().and_then(|h| h.parse().ok()) {
+            config.hot_hours = hours;
+        }
+
+        if let Some(secs) = std::env::var("ENGRAM_TAP_DEDUP_SECS").ok().and_then(|s| s.parse().ok()) {
+            config.tap_dedup_secs = secs;
+        }
+
+        if let Some(rate) = std::env::var("ENGRAM_TAP_EVENT_SAMPLE").ok().and_then(|r| r.parse::<f64>().ok()) {
+            if (0.0..=1.0).contains(&rate) {
+                config.event_policy.tap_sample_rate = rate;
+            }
+        }
+
+        config.embed_command = std::env::var("ENGRAM_EMBED_CMD").ok().filter(|c| !c.trim().is_empty());
+
+        if let Ok(path) = std::env::var("ENGRAM_CLAUDE_MD") {
+            config.claude_md_path = Some(PathBuf::from(path));
+        }
+
+        config.on_promote = std::env::var("ENGRAM_ON_PROMOTE").ok().filter(|c| !c.trim().is_empty());
+        config.on_forget = std::env::var("ENGRAM_ON_FORGET").ok().filter(|c| !c.trim().is_empty());
+
+        config.decay_half_life_days = std::env::var("ENGRAM_DECAY_HALF_LIFE_DAYS")
+            .ok()
+            .and_then(|d| d.parse::<f64>().ok())
+            .filter(|d| d.is_finite() && *d > 0.0);
+
+        config.edit_refreshes = std::env::var("ENGRAM_EDIT_REFRESHES")
+            .is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"));
+
+        config
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Memory {
     pub id: MemoryId,
     pub content: String,
     pub tap_count: u32,
     pub last_tapped_at: Option<String>,
     pub created_at: String,
+    /// Template kind; free-text memories are "plain"
+    pub kind: String,
+    /// Structured fields as a JSON object, for templated kinds
+    pub fields: Option<String>,
+    /// Pinned memories are exempt from automatic lifecycle changes
+    pub pinned: bool,
+    /// Bumped on every mutation, for optimistic concurrency checks
+    pub version: i64,
+    /// Display hint derived from the content: "url", "shell", "code" or "prose"
+    #[serde(skip_deserializing)]
+    pub content_kind: &'static str,
+    /// Where the memory was added, as "hostname/session-uuid"; None for
+    /// memories from before origins were recorded
+    pub origin: Option<String>,
 }
 
 pub fn open_db(config: &Config) -> Result<Connection> {
     let db_path = &config.db_path;
-    let conn = Connection::open(&db_path)?;
+    let conn = Connection::open(db_path)?;
 
     // Enable WAL mode for better concurrency
     conn.pragma_update(None, "journal_mode", "WAL")?;
@@ -46,14 +199,185 @@ pub fn open_db(config: &Config) -> Result<Connection> {
     // Set busy timeout to 5 seconds for retry-on-busy
     conn.busy_timeout(std::time::Duration::from_secs(5))?;
 
+    apply_pragmas(&conn, &config.pragmas)?;
+
     // Initialize schema
     init_schema(&conn)?;
 
     Ok(conn)
 }
 
+/// Apply user pragmas and read each one back. SQLite ignores unknown pragmas
+/// and some values silently (e.g. WAL on an in-memory DB), so anything that
+/// didn't take effect is an error rather than a surprise later.
+pub fn apply_pragmas(conn: &Connection, pragmas: &[(String, String)]) -> Result<()> {
+    for (name, value) in pragmas {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(misuse_error(format!("invalid pragma name '{}'", name)));
+        }
+
+        conn.pragma_update(None, name, value)?;
+
+        let actual: Option<String> = conn
+            .pragma_query_value(None, name, |row| {
+                let v: rusqlite::types::Value = row.get(0)?;
+                Ok(match v {
+                    rusqlite::types::Value::Integer(i) => i.to_string(),
+                    rusqlite::types::Value::Real(f) => f.to_string(),
+                    rusqlite::types::Value::Text(t) => t,
+                    _ => String::new(),
+                })
+            })
+            .ok();
+
+        match actual {
+            None => return Err(misuse_error(format!("unknown pragma '{}'", name))),
+            Some(actual) if !pragma_value_matches(name, value, &actual) => {
+                return Err(misuse_error(format!(
+                    "pragma {}={} did not take effect (value is {})",
+                    name, value, actual
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Compare a requested pragma value with what SQLite reports back,
+/// accounting for pragmas that report named settings as numbers
+fn pragma_value_matches(name: &str, requested: &str, actual: &str) -> bool {
+    if requested.eq_ignore_ascii_case(actual) {
+        return true;
+    }
+    let named: &[&str] = match name.to_ascii_lowercase().as_str() {
+        "synchronous" => &["off", "normal", "full", "extra"],
+        "temp_store" => &["default", "file", "memory"],
+        "auto_vacuum" => &["none", "full", "incremental"],
+        _ => &[],
+    };
+    named
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(requested))
+        .is_some_and(|i| i.to_string() == actual)
+}
+
+fn misuse_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+        Some(message),
+    )
+}
+
+fn conflict_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+        Some(message),
+    )
+}
+
+/// Open an existing database without write access.
+/// Skips WAL setup and schema init, so it never mutates the store.
+pub fn open_db_readonly(config: &Config) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        &config.db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+
+    // Readers still wait out a writer's lock instead of failing immediately
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
+    Ok(conn)
+}
+
 pub fn init_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(include_str!("init.sql"))?;
+    migrate(conn)?;
+    Ok(())
+}
+
+/// Bring databases created by older versions up to the current schema.
+/// init.sql only creates missing tables, so new columns are added here.
+fn migrate(conn: &Connection) -> Result<()> {
+    for (table, column, definition) in ADDED_COLUMNS {
+        if !column_exists(conn, table, column)? {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+        }
+    }
+    Ok(())
+}
+
+/// Columns added after the original schema, as (table, column, definition).
+/// Keep in sync with init.sql.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("memories", "kind", "TEXT NOT NULL DEFAULT 'plain'"),
+    ("memories", "fields", "TEXT"),
+    ("memories", "pinned", "INTEGER NOT NULL DEFAULT 0"),
+    ("memories", "version", "INTEGER NOT NULL DEFAULT 0"),
+    ("memories", "origin", "TEXT"),
+];
+
+/// Tables added after the original schema; init.sql creates them
+const ADDED_TABLES: &[&str] = &["memory_embeddings"];
+
+/// Whether the schema already has every table and column a writable open would add
+pub fn schema_is_current(conn: &Connection) -> Result<bool> {
+    for (table, column, _) in ADDED_COLUMNS {
+        if !column_exists(conn, table, column)? {
+            return Ok(false);
+        }
+    }
+    for table in ADDED_TABLES {
+        let exists = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get::<_, u32>(0).map(|n| n > 0),
+        )?;
+        if !exists {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get::<_, u32>(0).map(|n| n > 0),
+    )
+}
+
+/// Which events get written. Lifecycle state (active, promoted, forgotten)
+/// is derived from ADD/PROMOTE/FORGET and friends, so only tap events,
+/// which just feed hot and activity, can be thinned out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventPolicy {
+    /// Fraction of TAP/RETAP events to keep, from 0 (none) to 1 (all)
+    pub tap_sample_rate: f64,
+}
+
+impl Default for EventPolicy {
+    fn default() -> Self {
+        EventPolicy { tap_sample_rate: 1.0 }
+    }
+}
+
+impl EventPolicy {
+    /// Whether an event with this action should be written this time
+    fn keeps(&self, action: &str) -> bool {
+        match action {
+            "TAP" | "RETAP" if self.tap_sample_rate < 1.0 => fastrand::f64() < self.tap_sample_rate,
+            _ => true,
+        }
+    }
+}
+
+/// Log an event unless `policy` samples it out
+fn log_event_with(conn: &Connection, policy: &EventPolicy, action: &str, memory_id: Option<&str>, data: Option<&str>) -> Result<()> {
+    if policy.keeps(action) {
+        log_event(conn, action, memory_id, data)?;
+    }
     Ok(())
 }
 
@@ -66,8 +390,53 @@ pub fn log_event(conn: &Connection, action: &str, memory_id: Option<&str>, data:
     Ok(())
 }
 
-#[derive(Debug)]
+/// Actions engram writes itself. Custom events can't reuse these names,
+/// since counts and terminal states are derived from them.
+const BUILTIN_ACTIONS: &[&str] = &[
+    "ADD", "TAP", "RETAP", "EDIT", "PROMOTE", "FORGET", "REVIVE", "PIN", "UNPIN", "REMOVE", "REVIEW", "EXPIRE",
+];
+
+/// Built-in actions as a quoted SQL list for `action IN (...)`. The names
+/// are constants, so inlining them is safe.
+fn builtin_action_list() -> String {
+    BUILTIN_ACTIONS
+        .iter()
+        .map(|a| format!("'{}'", a))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// PROMOTE and FORGET events still in effect, as (memory_id, action) rows.
+/// A FORGET stops counting once a later REVIVE brings the memory back.
+const TERMINAL_EVENTS: &str = "SELECT t.memory_id, t.action FROM events AS t
+     WHERE t.action IN ('PROMOTE', 'FORGET') AND t.memory_id IS NOT NULL
+       AND NOT EXISTS (
+           SELECT 1 FROM events AS r
+           WHERE r.action = 'REVIVE' AND r.memory_id = t.memory_id AND r.id > t.id
+       )";
+
+/// Log an integration-defined event, e.g. a DEPLOY marker.
+/// The action must be uppercase alphanumeric and not a built-in action.
+pub fn log_custom_event(conn: &Connection, action: &str, memory_id: Option<&str>, data: &str) -> Result<()> {
+    let valid = action.starts_with(|c: char| c.is_ascii_uppercase())
+        && action.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if !valid {
+        return Err(misuse_error(format!("invalid action '{}': use uppercase letters and digits", action)));
+    }
+    if BUILTIN_ACTIONS.contains(&action) {
+        return Err(misuse_error(format!("action '{}' is reserved", action)));
+    }
+    if let Some(id) = memory_id {
+        if get_memory(conn, id)?.is_none() {
+            return Err(misuse_error(format!("memory not found: {}", id)));
+        }
+    }
+    log_event(conn, action, memory_id, Some(data))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Event {
+    pub id: i64,
     pub timestamp: String,
     pub action: String,
     pub memory_id: Option<MemoryId>,
@@ -75,45 +444,158 @@ pub struct Event {
 }
 
 /// Get events from the event log
+///
+/// `grep` matches a literal substring of the event data.
+/// `hide_promoted_taps` drops TAP events for promoted memories before the
+/// limit applies, so a full page still means there may be older events.
 pub fn get_events(
     conn: &Connection,
     limit: u32,
     action: Option<&str>,
+    exclude_action: Option<&str>,
     memory_id: Option<&str>,
+    grep: Option<&str>,
+    hide_promoted_taps: bool,
 ) -> Result<Vec<Event>> {
     let mut sql = String::from(
-        "SELECT timestamp, action, memory_id, data FROM events WHERE 1=1"
+        "SELECT id, timestamp, action, memory_id, data FROM events WHERE 1=1"
     );
-    let mut param_idx = 1;
+    // Every value is bound; only the placeholder numbers are formatted in
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
 
-    if action.is_some() {
-        sql.push_str(&format!(" AND action = ?{}", param_idx));
-        param_idx += 1;
+    if let Some(a) = action {
+        values.push(a.to_string().into());
+        sql.push_str(&format!(" AND action = ?{}", values.len()));
+    }
+    if let Some(a) = exclude_action {
+        values.push(a.to_string().into());
+        sql.push_str(&format!(" AND action != ?{}", values.len()));
+    }
+    if let Some(m) = memory_id {
+        values.push(m.to_string().into());
+        sql.push_str(&format!(" AND memory_id = ?{}", values.len()));
     }
-    if memory_id.is_some() {
-        sql.push_str(&format!(" AND memory_id = ?{}", param_idx));
+    if let Some(g) = grep {
+        values.push(format!("%{}%", escape_like(g)).into());
+        sql.push_str(&format!(" AND data LIKE ?{} ESCAPE '\\'", values.len()));
+    }
+    if hide_promoted_taps {
+        sql.push_str(
+            " AND NOT (action = 'TAP' AND memory_id IN
+                 (SELECT memory_id FROM events WHERE action = 'PROMOTE' AND memory_id IS NOT NULL))"
+        );
     }
 
-    sql.push_str(&format!(" ORDER BY id DESC LIMIT {}", limit));
+    values.push(i64::from(limit).into());
+    sql.push_str(&format!(" ORDER BY id DESC LIMIT ?{}", values.len()));
 
     let mut stmt = conn.prepare(&sql)?;
-
-    let events = match (action, memory_id) {
-        (Some(a), Some(m)) => stmt.query_map(params![a, m], row_to_event)?,
-        (Some(a), None) => stmt.query_map(params![a], row_to_event)?,
-        (None, Some(m)) => stmt.query_map(params![m], row_to_event)?,
-        (None, None) => stmt.query_map([], row_to_event)?,
-    };
-
+    let events = stmt.query_map(rusqlite::params_from_iter(values), row_to_event)?;
     events.collect()
 }
 
+/// Escape LIKE wildcards so user input matches literally (pair with `ESCAPE '\'`)
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
 fn row_to_event(row: &rusqlite::Row) -> rusqlite::Result<Event> {
     Ok(Event {
-        timestamp: row.get(0)?,
-        action: row.get(1)?,
-        memory_id: row.get(2)?,
-        data: row.get(3)?,
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        action: row.get(2)?,
+        memory_id: row.get(3)?,
+        data: lossy_text(row, 4)?,
+    })
+}
+
+/// Read a TEXT column that may hold invalid UTF-8, replacing bad bytes
+fn lossy_text(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<String>> {
+    Ok(row
+        .get_ref(idx)?
+        .as_bytes_or_null()?
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// Escape raw control characters, the part older hand-built event JSON
+/// left out. Quotes and backslashes were already escaped.
+fn escape_raw_controls(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    for c in data.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Built-in events whose data isn't valid UTF-8 JSON, each paired with a
+/// repaired replacement. Data that can't be re-escaped into an object is
+/// kept as a JSON string so nothing is lost. Custom events carry free text
+/// and are never flagged.
+pub fn malformed_events(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, data FROM events WHERE data IS NOT NULL AND action IN ({}) ORDER BY id",
+        builtin_action_list()
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        let bytes = row.get_ref(1)?.as_bytes()?.to_vec();
+        Ok((row.get::<_, i64>(0)?, bytes))
+    })?;
+
+    let mut malformed = Vec::new();
+    for row in rows {
+        let (id, bytes) = row?;
+        if let Ok(text) = std::str::from_utf8(&bytes) {
+            if serde_json::from_str::<serde_json::Value>(text).is_ok() {
+                continue;
+            }
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let escaped = escape_raw_controls(&text);
+        let repaired = if serde_json::from_str::<serde_json::Value>(&escaped).is_ok() {
+            escaped
+        } else {
+            serde_json::Value::String(text.into_owned()).to_string()
+        };
+        malformed.push((id, repaired));
+    }
+    Ok(malformed)
+}
+
+/// Rewrite every malformed event's data in one transaction.
+/// Returns how many events were repaired.
+pub fn repair_events(conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let malformed = malformed_events(&tx)?;
+    for (id, data) in &malformed {
+        tx.execute("UPDATE events SET data = ?1 WHERE id = ?2", params![data, id])?;
+    }
+    tx.commit()?;
+    Ok(malformed.len())
+}
+
+/// Columns selected for a `Memory`, in the order `row_to_memory` reads them
+const MEMORY_COLUMNS: &str = "id, content, tap_count, last_tapped_at, created_at, kind, fields, pinned, version, origin";
+
+fn row_to_memory(row: &rusqlite::Row) -> rusqlite::Result<Memory> {
+    let content: String = row.get(1)?;
+    Ok(Memory {
+        id: row.get(0)?,
+        content_kind: crate::content_kind::detect_content_kind(&content),
+        content,
+        tap_count: row.get(2)?,
+        last_tapped_at: row.get(3)?,
+        created_at: row.get(4)?,
+        kind: row.get(5)?,
+        fields: row.get(6)?,
+        pinned: row.get(7)?,
+        version: row.get(8)?,
+        origin: row.get(9)?,
     })
 }
 
@@ -133,85 +615,302 @@ fn now_timestamp() -> String {
 
 // CRUD operations
 
+#[cfg(test)]
 pub fn add_memory(conn: &Connection, content: &str) -> Result<MemoryId> {
+    add_structured_memory(conn, content, "plain", None, None)
+}
+
+/// Add a memory of a template kind, with its fields as a JSON object.
+/// `content` holds the human-readable summary.
+pub fn add_structured_memory(
+    conn: &Connection,
+    content: &str,
+    kind: &str,
+    fields: Option<&str>,
+    origin: Option<&str>,
+) -> Result<MemoryId> {
     let id = generate_id(content);
     let created_at = now_timestamp();
 
     conn.execute(
-        "INSERT INTO memories (id, content, tap_count, created_at)
-         VALUES (?1, ?2, 0, ?3)",
-        params![id, content, created_at],
+        "INSERT INTO memories (id, content, tap_count, created_at, kind, fields, origin)
+         VALUES (?1, ?2, 0, ?3, ?4, ?5, ?6)",
+        params![id, content, created_at, kind, fields, origin],
     )?;
 
     // Log ADD event
-    let data = format!(r#"{{"content":"{}"}}"#,
-        content.replace('\\', "\\\\").replace('"', "\\\""));
+    let data = serde_json::json!({ "content": content }).to_string();
     log_event(conn, "ADD", Some(&id), Some(&data))?;
 
     Ok(id)
 }
 
+/// Add plain memories in one transaction. Content that is already stored,
+/// or repeats earlier in the batch, isn't added again. Returns each entry's
+/// ID and whether it was newly added.
+pub fn add_memories(conn: &Connection, contents: &[&str], origin: Option<&str>) -> Result<Vec<(MemoryId, bool)>> {
+    let tx = conn.unchecked_transaction()?;
+    let mut results = Vec::with_capacity(contents.len());
+    for content in contents {
+        let id = generate_id(content);
+        let exists = get_memory(&tx, &id)?.is_some();
+        if !exists {
+            add_structured_memory(&tx, content, "plain", None, origin)?;
+        }
+        results.push((id, !exists));
+    }
+    tx.commit()?;
+    Ok(results)
+}
+
 pub fn get_memory(conn: &Connection, id: &str) -> Result<Option<Memory>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, content, tap_count, last_tapped_at, created_at
-         FROM memories WHERE id = ?1"
-    )?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM memories WHERE id = ?1", MEMORY_COLUMNS))?;
 
     let mut rows = stmt.query(params![id])?;
 
     if let Some(row) = rows.next()? {
-        Ok(Some(Memory {
-            id: row.get(0)?,
-            content: row.get(1)?,
-            tap_count: row.get(2)?,
-            last_tapped_at: row.get(3)?,
-            created_at: row.get(4)?,
-        }))
+        Ok(Some(row_to_memory(row)?))
     } else {
         Ok(None)
     }
 }
 
+/// Fetch several memories at once, keyed by ID; unknown IDs are left out
+pub fn get_memories_by_ids(conn: &Connection, ids: &[&str]) -> Result<std::collections::HashMap<MemoryId, Memory>> {
+    let mut memories = std::collections::HashMap::new();
+    // Stay well under SQLite's limit on bound parameters per statement
+    for chunk in ids.chunks(500) {
+        let placeholders = (1..=chunk.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM memories WHERE id IN ({})",
+            MEMORY_COLUMNS, placeholders
+        ))?;
+        for memory in stmt.query_map(rusqlite::params_from_iter(chunk), row_to_memory)? {
+            let memory = memory?;
+            memories.insert(memory.id.clone(), memory);
+        }
+    }
+    Ok(memories)
+}
+
 pub fn list_memories(conn: &Connection) -> Result<Vec<Memory>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, content, tap_count, last_tapped_at, created_at
-         FROM memories
-         ORDER BY tap_count DESC, created_at DESC"
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories ORDER BY tap_count DESC, created_at DESC",
+        MEMORY_COLUMNS
+    ))?;
 
-    let memories = stmt.query_map([], |row| {
-        Ok(Memory {
-            id: row.get(0)?,
-            content: row.get(1)?,
-            tap_count: row.get(2)?,
-            last_tapped_at: row.get(3)?,
-            created_at: row.get(4)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    let memories = stmt.query_map([], row_to_memory)?.collect::<Result<Vec<_>>>()?;
 
     Ok(memories)
 }
 
-/// Forget a memory (terminal state - discarded)
+/// Visit memories one row at a time, without collecting them, in `list` order.
+/// The callback can stop early by returning `ControlFlow::Break`.
+pub fn for_each_memory<F>(conn: &Connection, include_terminal: bool, mut f: F) -> Result<()>
+where
+    F: FnMut(Memory) -> ControlFlow<()>,
+{
+    let filter = if include_terminal {
+        String::new()
+    } else {
+        format!("WHERE id NOT IN (SELECT memory_id FROM ({}))", TERMINAL_EVENTS)
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories {} ORDER BY tap_count DESC, created_at DESC",
+        MEMORY_COLUMNS, filter
+    ))?;
+
+    for memory in stmt.query_map([], row_to_memory)? {
+        if f(memory?).is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Search memories whose content contains `pattern` (case-insensitive, literal)
+pub fn search_memories(conn: &Connection, pattern: &str) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories WHERE content LIKE ?1 ESCAPE '\\'
+         ORDER BY tap_count DESC, created_at DESC",
+        MEMORY_COLUMNS
+    ))?;
+
+    let search = format!("%{}%", escape_like(pattern));
+    let memories = stmt.query_map(params![search], row_to_memory)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(memories)
+}
+
+/// Forget a memory (terminal state - discarded). The event carries a
+/// snapshot of the memory as it was, so it can be restored even if the
+/// row is later edited or lost. Returns false, logging nothing, unless
+/// the memory is active.
 pub fn forget_memory(conn: &Connection, id: &str) -> Result<bool> {
-    // Check if memory exists
-    let exists: bool = conn.query_row(
-        "SELECT 1 FROM memories WHERE id = ?1",
-        params![id],
-        |_| Ok(true),
-    ).unwrap_or(false);
+    if lifecycle(conn, id)? != Some(Lifecycle::Active) || !bump_version(conn, id)? {
+        return Ok(false);
+    }
+    let data = get_memory(conn, id)?.map(|m| memory_snapshot(&m));
+    log_event(conn, "FORGET", Some(id), data.as_deref())?;
+    Ok(true)
+}
+
+/// Where a memory stands, derived from its PROMOTE/FORGET/REVIVE events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lifecycle {
+    Active,
+    Promoted,
+    Forgotten,
+}
+
+impl Lifecycle {
+    pub fn label(self) -> &'static str {
+        match self {
+            Lifecycle::Active => "active",
+            Lifecycle::Promoted => "promoted",
+            Lifecycle::Forgotten => "forgotten",
+        }
+    }
+}
+
+/// IDs whose lifecycle is `Forgotten`, as a subquery: forgotten, not
+/// revived since, and never promoted
+fn forgotten_ids_sql() -> String {
+    format!(
+        "SELECT memory_id FROM ({0}) WHERE action = 'FORGET'
+         EXCEPT SELECT memory_id FROM ({0}) WHERE action = 'PROMOTE'",
+        TERMINAL_EVENTS
+    )
+}
 
-    if exists {
-        log_event(conn, "FORGET", Some(id), None)?;
-        Ok(true)
+/// A memory's lifecycle state, or None if it doesn't exist. Promotion
+/// wins over forgetting, since only a forget can be revived.
+pub fn lifecycle(conn: &Connection, id: &str) -> Result<Option<Lifecycle>> {
+    if get_memory(conn, id)?.is_none() {
+        return Ok(None);
+    }
+    let mut stmt = conn.prepare(&format!("SELECT action FROM ({}) WHERE memory_id = ?1", TERMINAL_EVENTS))?;
+    let actions = stmt.query_map(params![id], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>>>()?;
+    Ok(Some(if actions.iter().any(|a| a == "PROMOTE") {
+        Lifecycle::Promoted
+    } else if actions.is_empty() {
+        Lifecycle::Active
     } else {
-        Ok(false)
+        Lifecycle::Forgotten
+    }))
+}
+
+/// Bring a forgotten memory back to active, logging REVIVE. Returns false
+/// (and does nothing) unless the memory is currently forgotten.
+pub fn revive_memory(conn: &Connection, id: &str) -> Result<bool> {
+    if lifecycle(conn, id)? != Some(Lifecycle::Forgotten) {
+        return Ok(false);
+    }
+    bump_version(conn, id)?;
+    log_event(conn, "REVIVE", Some(id), None)?;
+    Ok(true)
+}
+
+/// Every stored field of a memory as a JSON object that deserializes back
+/// into a `Memory`
+fn memory_snapshot(memory: &Memory) -> String {
+    serde_json::json!({
+        "id": memory.id,
+        "content": memory.content,
+        "tap_count": memory.tap_count,
+        "last_tapped_at": memory.last_tapped_at,
+        "created_at": memory.created_at,
+        "kind": memory.kind,
+        "fields": memory.fields,
+        "pinned": memory.pinned,
+        "version": memory.version,
+        "origin": memory.origin,
+    })
+    .to_string()
+}
+
+/// Predicates for bulk forgetting. Unset fields don't filter.
+#[derive(Debug, Default)]
+pub struct ForgetFilter {
+    /// Only memories created before this RFC 3339 timestamp
+    pub created_before: Option<String>,
+    /// Only memories tapped at most this many times
+    pub max_taps: Option<u32>,
+}
+
+/// Active, unpinned memories matching every predicate in `filter`
+pub fn memories_where(conn: &Connection, filter: &ForgetFilter) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories
+         WHERE pinned = 0
+           AND (?1 IS NULL OR datetime(created_at) < datetime(?1))
+           AND (?2 IS NULL OR tap_count <= ?2)
+         ORDER BY created_at",
+        MEMORY_COLUMNS
+    ))?;
+    let memories = stmt
+        .query_map(params![filter.created_before, filter.max_taps], row_to_memory)?
+        .collect::<Result<Vec<_>>>()?;
+    exclude_terminal(conn, memories, false)
+}
+
+/// The memories among `ids` that still exist and are active, in `ids` order
+fn active_memories(conn: &Connection, ids: &[MemoryId]) -> Result<Vec<Memory>> {
+    let mut active = Vec::new();
+    for id in ids {
+        if lifecycle(conn, id)? == Some(Lifecycle::Active) {
+            active.extend(get_memory(conn, id)?);
+        }
     }
+    Ok(active)
+}
+
+/// Forget exactly these memories in one transaction, skipping any that
+/// were removed or left the active state since they were picked.
+/// Returns the memories that were forgotten.
+pub fn forget_memories(conn: &Connection, ids: &[MemoryId]) -> Result<Vec<Memory>> {
+    let tx = conn.unchecked_transaction()?;
+    let memories = active_memories(&tx, ids)?;
+    for m in &memories {
+        forget_memory(&tx, &m.id)?;
+    }
+    tx.commit()?;
+    Ok(memories)
+}
+
+/// Promote exactly these memories in one transaction, skipping any that
+/// are no longer active. Returns the memories that were promoted.
+pub fn promote_memories(conn: &Connection, ids: &[MemoryId]) -> Result<Vec<Memory>> {
+    let tx = conn.unchecked_transaction()?;
+    let memories = active_memories(&tx, ids)?;
+    for m in &memories {
+        promote_memory(&tx, &m.id)?;
+    }
+    tx.commit()?;
+    Ok(memories)
+}
+
+/// Promote one memory, forgotten or not, with the read and the write in
+/// one transaction. Returns the memory as it was promoted, or None if it
+/// doesn't exist or is already promoted.
+pub fn promote_one(conn: &Connection, id: &str) -> Result<Option<Memory>> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+    let memory = get_memory(&tx, id)?;
+    let promoted = match memory {
+        Some(m) if promote_memory(&tx, id)?.is_some() => Some(m),
+        _ => None,
+    };
+    tx.commit()?;
+    Ok(promoted)
 }
 
 /// Promote a memory (terminal state - graduated to CLAUDE.md)
-/// Returns the memory content for inclusion in CLAUDE.md
+/// Returns the memory content for inclusion in CLAUDE.md, or None (and
+/// logs nothing) if it doesn't exist or is already promoted
 pub fn promote_memory(conn: &Connection, id: &str) -> Result<Option<String>> {
+    if lifecycle(conn, id)? == Some(Lifecycle::Promoted) {
+        return Ok(None);
+    }
     let content: Option<String> = conn.query_row(
         "SELECT content FROM memories WHERE id = ?1",
         params![id],
@@ -219,44 +918,77 @@ pub fn promote_memory(conn: &Connection, id: &str) -> Result<Option<String>> {
     ).ok();
 
     if let Some(ref c) = content {
-        let data = format!(r#"{{"content":"{}"}}"#,
-            c.replace('\\', "\\\\").replace('"', "\\\""));
+        bump_version(conn, id)?;
+        let data = serde_json::json!({ "content": c }).to_string();
         log_event(conn, "PROMOTE", Some(id), Some(&data))?;
     }
 
     Ok(content)
 }
 
-/// Get memory IDs that have reached a terminal state (PROMOTE or FORGET)
+/// Get memory IDs that are in a terminal state (promoted, or forgotten
+/// and not revived)
 pub fn get_terminal_memory_ids(conn: &Connection) -> Result<Vec<MemoryId>> {
-    let mut stmt = conn.prepare(
-        "SELECT DISTINCT memory_id FROM events WHERE action IN ('PROMOTE', 'FORGET') AND memory_id IS NOT NULL"
-    )?;
+    let mut stmt = conn.prepare(&format!("SELECT DISTINCT memory_id FROM ({})", TERMINAL_EVENTS))?;
     let ids = stmt.query_map([], |row| row.get(0))?
         .collect::<Result<Vec<_>>>()?;
     Ok(ids)
 }
 
-/// Get memory IDs that have been promoted
-pub fn get_promoted_memory_ids(conn: &Connection) -> Result<Vec<MemoryId>> {
-    let mut stmt = conn.prepare(
-        "SELECT DISTINCT memory_id FROM events WHERE action = 'PROMOTE' AND memory_id IS NOT NULL"
-    )?;
-    let ids = stmt.query_map([], |row| row.get(0))?
-        .collect::<Result<Vec<_>>>()?;
-    Ok(ids)
+/// Promoted memories in the order they were first promoted
+pub fn list_promoted_memories(conn: &Connection) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories
+         JOIN (
+             SELECT memory_id, MIN(id) AS first_promote FROM events
+             WHERE action = 'PROMOTE'
+             GROUP BY memory_id
+         ) promoted ON promoted.memory_id = memories.id
+         ORDER BY first_promote",
+        MEMORY_COLUMNS
+    ))?;
+    let memories = stmt.query_map([], row_to_memory)?.collect::<Result<Vec<_>>>()?;
+    Ok(memories)
 }
 
 /// List memories, optionally excluding those in terminal states
 pub fn list_memories_filtered(conn: &Connection, include_terminal: bool) -> Result<Vec<Memory>> {
     let all_memories = list_memories(conn)?;
+    exclude_terminal(conn, all_memories, include_terminal)
+}
+
+/// Search memories, optionally excluding those in terminal states
+pub fn search_memories_filtered(conn: &Connection, pattern: &str, include_terminal: bool) -> Result<Vec<Memory>> {
+    let matches = search_memories(conn, pattern)?;
+    exclude_terminal(conn, matches, include_terminal)
+}
 
+/// Whether `word` occurs in `text` with no letter, digit or underscore on
+/// either side. Case-insensitive for ASCII, like LIKE.
+pub fn contains_word(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let text_lower = text.to_ascii_lowercase();
+    let word_lower = word.to_ascii_lowercase();
+    text_lower.match_indices(&word_lower).any(|(start, matched)| {
+        let before = text_lower[..start].chars().next_back();
+        let after = text_lower[start + matched.len()..].chars().next();
+        // A word made of punctuation only needs to be found
+        let starts_word = !word.starts_with(is_word_char) || !before.is_some_and(is_word_char);
+        let ends_word = !word.ends_with(is_word_char) || !after.is_some_and(is_word_char);
+        starts_word && ends_word
+    })
+}
+
+fn exclude_terminal(conn: &Connection, memories: Vec<Memory>, include_terminal: bool) -> Result<Vec<Memory>> {
     if include_terminal {
-        return Ok(all_memories);
+        return Ok(memories);
     }
 
     let terminal_ids = get_terminal_memory_ids(conn)?;
-    let filtered: Vec<Memory> = all_memories
+    let filtered: Vec<Memory> = memories
         .into_iter()
         .filter(|m| !terminal_ids.contains(&m.id))
         .collect();
@@ -264,61 +996,564 @@ pub fn list_memories_filtered(conn: &Connection, include_terminal: bool) -> Resu
     Ok(filtered)
 }
 
-/// Edit a memory's content
-pub fn edit_memory(conn: &Connection, id: &str, new_content: &str) -> Result<bool> {
-    // Get old content for event log
-    let old_content: Option<String> = conn.query_row(
-        "SELECT content FROM memories WHERE id = ?1",
+/// A memory with its tap count inside a recent window
+#[derive(Debug)]
+pub struct HotMemory {
+    pub memory: Memory,
+    pub recent_taps: u32,
+}
+
+/// Get memories tapped at or after `since` (RFC3339), most-tapped first.
+/// Future-dated taps are ignored so they can't stay hot forever.
+pub fn get_hot_memories(conn: &Connection, since: &str) -> Result<Vec<HotMemory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, recent FROM memories
+         JOIN (
+             SELECT memory_id, COUNT(*) AS recent FROM events
+             WHERE action = 'TAP' AND datetime(timestamp) >= datetime(?1)
+               AND datetime(timestamp) <= datetime('now')
+             GROUP BY memory_id
+         ) hot ON hot.memory_id = memories.id
+         ORDER BY recent DESC, last_tapped_at DESC",
+        MEMORY_COLUMNS
+    ))?;
+
+    let hot = stmt.query_map(params![since], |row| {
+        Ok(HotMemory {
+            memory: row_to_memory(row)?,
+            recent_taps: row.get("recent")?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(hot)
+}
+
+/// Get memories added at or after `since` (RFC3339), going by their ADD
+/// events. Future-dated events are ignored, as in `get_hot_memories`.
+pub fn get_added_memories(conn: &Connection, since: &str) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM memories WHERE id IN (
+             SELECT memory_id FROM events
+             WHERE action = 'ADD' AND datetime(timestamp) >= datetime(?1)
+               AND datetime(timestamp) <= datetime('now')
+         )",
+        MEMORY_COLUMNS
+    ))?;
+    let added = stmt.query_map(params![since], row_to_memory)?.collect::<Result<Vec<_>>>()?;
+    Ok(added)
+}
+
+/// Event counts for one calendar day
+#[derive(Debug, Default)]
+pub struct DayActivity {
+    /// Local date, YYYY-MM-DD
+    pub day: String,
+    /// One count per requested action, in the order they were asked for
+    pub counts: Vec<u32>,
+}
+
+/// Pseudo-action for `get_activity_by_day` that counts all custom events
+pub const CUSTOM_ACTIONS: &str = "CUSTOM";
+
+/// Count events per day from `since_day` (YYYY-MM-DD) onward, oldest first,
+/// with one column per entry in `actions`. Days with no events are omitted,
+/// and so are future-dated events (clock skew, imported stores).
+pub fn get_activity_by_day(conn: &Connection, since_day: &str, actions: &[String]) -> Result<Vec<DayActivity>> {
+    let builtins = builtin_action_list();
+
+    // Actions are bound as parameters after since_day (?1)
+    let mut bound: Vec<&str> = vec![since_day];
+    let mut columns = String::new();
+    for action in actions {
+        if action == CUSTOM_ACTIONS {
+            columns.push_str(&format!(", SUM(CASE WHEN action NOT IN ({}) THEN 1 ELSE 0 END)", builtins));
+        } else {
+            bound.push(action);
+            columns.push_str(&format!(", SUM(CASE WHEN action = ?{} THEN 1 ELSE 0 END)", bound.len()));
+        }
+    }
+
+    // Timestamps are written in local time, so their date prefix is the local day
+    let mut stmt = conn.prepare(&format!(
+        "SELECT substr(timestamp, 1, 10) AS day{}
+         FROM events
+         WHERE substr(timestamp, 1, 10) >= ?1 AND datetime(timestamp) <= datetime('now')
+         GROUP BY day
+         ORDER BY day",
+        columns
+    ))?;
+
+    let days = stmt.query_map(rusqlite::params_from_iter(bound), |row| {
+        Ok(DayActivity {
+            day: row.get(0)?,
+            counts: (1..=actions.len()).map(|i| row.get(i)).collect::<Result<_>>()?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(days)
+}
+
+/// Active memories at the end of a day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayPopulation {
+    /// Local date, YYYY-MM-DD
+    pub day: String,
+    pub active: u32,
+}
+
+/// Replay ADD/PROMOTE/FORGET/REVIVE events to get the active population
+/// at the end of each day that changed it, oldest first
+pub fn get_stats_trend(conn: &Connection) -> Result<Vec<DayPopulation>> {
+    let mut stmt = conn.prepare(
+        "SELECT substr(timestamp, 1, 10), action, memory_id FROM events
+         WHERE action IN ('ADD', 'PROMOTE', 'FORGET', 'REVIVE') AND memory_id IS NOT NULL
+           AND datetime(timestamp) <= datetime('now')
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+    })?;
+
+    let mut active = std::collections::HashSet::new();
+    let mut days: Vec<DayPopulation> = Vec::new();
+    for row in rows {
+        let (day, action, memory_id) = row?;
+        if action == "ADD" || action == "REVIVE" {
+            active.insert(memory_id);
+        } else {
+            active.remove(&memory_id);
+        }
+        let population = DayPopulation { day, active: active.len() as u32 };
+        match days.last_mut() {
+            Some(last) if last.day == population.day => *last = population,
+            _ => days.push(population),
+        }
+    }
+    Ok(days)
+}
+
+/// Number of events logged per action, by action name
+pub fn count_events_by_action(conn: &Connection) -> Result<Vec<(String, u64)>> {
+    let mut stmt = conn.prepare("SELECT action, COUNT(*) FROM events GROUP BY action ORDER BY action")?;
+    let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    counts.collect()
+}
+
+/// Aggregate statistics about the store
+#[derive(Debug, Serialize)]
+pub struct MemoryStats {
+    pub total_memories: u32,
+    pub active: u32,
+    pub promoted: u32,
+    pub forgotten: u32,
+    /// Active memories that have never been tapped
+    pub never_tapped: u32,
+    pub total_taps: u64,
+    pub total_events: u64,
+    pub db_size_bytes: u64,
+    pub wal_size_bytes: Option<u64>,
+}
+
+/// Compute store statistics, including on-disk size
+pub fn get_stats(conn: &Connection) -> Result<MemoryStats> {
+    let (total_memories, total_taps): (u32, u64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(tap_count), 0) FROM memories",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let count_terminal = |action: &str| -> Result<u32> {
+        conn.query_row(
+            &format!("SELECT COUNT(DISTINCT memory_id) FROM ({}) WHERE action = ?1", TERMINAL_EVENTS),
+            params![action],
+            |row| row.get(0),
+        )
+    };
+    let promoted = count_terminal("PROMOTE")?;
+    let forgotten = count_terminal("FORGET")?;
+
+    let (active, never_tapped): (u32, u32) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(tap_count = 0), 0) FROM memories
+             WHERE id NOT IN (SELECT memory_id FROM ({}))",
+            TERMINAL_EVENTS
+        ),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let total_events: u64 = conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
+
+    let page_count: u64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+    let page_size: u64 = conn.pragma_query_value(None, "page_size", |row| row.get(0))?;
+
+    // WAL lives next to the main file; absent for in-memory or checkpointed DBs
+    let wal_size_bytes = conn
+        .path()
+        .filter(|p| !p.is_empty())
+        .and_then(|p| fs::metadata(format!("{}-wal", p)).ok())
+        .map(|m| m.len());
+
+    Ok(MemoryStats {
+        total_memories,
+        active,
+        promoted,
+        forgotten,
+        never_tapped,
+        total_taps,
+        total_events,
+        db_size_bytes: page_count * page_size,
+        wal_size_bytes,
+    })
+}
+
+/// Bump a memory's version without changing anything else.
+/// Returns false if the memory doesn't exist.
+fn bump_version(conn: &Connection, id: &str) -> Result<bool> {
+    let rows_affected = conn.execute(
+        "UPDATE memories SET version = version + 1 WHERE id = ?1",
         params![id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Current `StoreExport` format
+const EXPORT_VERSION: u32 = 1;
+
+/// Every memory and event in a store, with all metadata, for moving a
+/// store between machines. Lifecycle state (promoted, forgotten) lives in
+/// the events, so they're always included.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreExport {
+    pub version: u32,
+    pub memories: Vec<Memory>,
+    pub events: Vec<Event>,
+}
+
+/// Snapshot the whole store, events in log order
+pub fn export_store(conn: &Connection) -> Result<StoreExport> {
+    let mut stmt = conn.prepare("SELECT id, timestamp, action, memory_id, data FROM events ORDER BY id")?;
+    let events = stmt.query_map([], row_to_event)?.collect::<Result<Vec<_>>>()?;
+    Ok(StoreExport { version: EXPORT_VERSION, memories: list_memories(conn)?, events })
+}
+
+/// Insert an export's memories verbatim, keeping their IDs, plus the
+/// events that belong to them, in one transaction. IDs come from content,
+/// so a memory that already exists is the same memory: it's skipped along
+/// with its events. Returns (imported, skipped) memory counts.
+pub fn import_store(conn: &Connection, export: &StoreExport) -> Result<(usize, usize)> {
+    if export.version != EXPORT_VERSION {
+        return Err(misuse_error(format!("unsupported export version {}", export.version)));
+    }
+    let tx = conn.unchecked_transaction()?;
+    let mut imported = std::collections::HashSet::new();
+    for m in &export.memories {
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO memories
+                 (id, content, tap_count, last_tapped_at, created_at, kind, fields, pinned, version, origin)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                m.id, m.content, m.tap_count, m.last_tapped_at, m.created_at,
+                m.kind, m.fields, m.pinned, m.version, m.origin
+            ],
+        )?;
+        if inserted > 0 {
+            imported.insert(m.id.as_str());
+        }
+    }
+    for e in &export.events {
+        // Events about memories that were skipped would double-count them
+        if e.memory_id.as_deref().is_some_and(|id| !imported.contains(id)) {
+            continue;
+        }
+        // Standalone events (e.g. DEPLOY markers) are skipped if already logged
+        if e.memory_id.is_none() {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM events
+                 WHERE memory_id IS NULL AND timestamp = ?1 AND action = ?2 AND data IS ?3)",
+                params![e.timestamp, e.action, e.data],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
+        }
+        tx.execute(
+            "INSERT INTO events (timestamp, action, memory_id, data) VALUES (?1, ?2, ?3, ?4)",
+            params![e.timestamp, e.action, e.memory_id, e.data],
+        )?;
+    }
+    tx.commit()?;
+    Ok((imported.len(), export.memories.len() - imported.len()))
+}
+
+/// Diagnostics for `engram doctor`, gathered without writing anything
+#[derive(Debug, Serialize)]
+pub struct Health {
+    pub db_path: String,
+    /// Whether every migration has been applied
+    pub schema_current: bool,
+    pub sqlite_version: &'static str,
+    pub fts5_available: bool,
+    pub journal_mode: String,
+    /// Events pointing at a memory that no longer exists
+    pub orphaned_events: u64,
+    /// Events whose data isn't valid JSON; see `repair_events`
+    pub malformed_events: usize,
+    /// Rows dated after now, from clock skew or an imported store. They're
+    /// left out of hot and activity until their time comes.
+    pub future_events: u64,
+    pub future_memories: u64,
+    pub stats: MemoryStats,
+}
+
+/// Run every read-only health check against the store
+pub fn check_health(conn: &Connection) -> Result<Health> {
+    let fts5_available = conn.query_row(
+        "SELECT sqlite_compileoption_used('ENABLE_FTS5')",
+        [],
+        |row| row.get::<_, bool>(0),
+    )?;
+    let journal_mode: String = conn.pragma_query_value(None, "journal_mode", |row| row.get(0))?;
+    let orphaned_events: u64 = conn.query_row(
+        "SELECT COUNT(*) FROM events
+         WHERE memory_id IS NOT NULL AND memory_id NOT IN (SELECT id FROM memories)",
+        [],
         |row| row.get(0),
+    )?;
+
+    let count_future = |table: &str, column: &str| -> Result<u64> {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE datetime({}) > datetime('now')", table, column),
+            [],
+            |row| row.get(0),
+        )
+    };
+
+    Ok(Health {
+        future_events: count_future("events", "timestamp")?,
+        future_memories: count_future("memories", "created_at")?,
+        db_path: conn.path().filter(|p| !p.is_empty()).unwrap_or(":memory:").to_string(),
+        schema_current: schema_is_current(conn)?,
+        sqlite_version: rusqlite::version(),
+        fts5_available,
+        journal_mode,
+        orphaned_events,
+        malformed_events: malformed_events(conn)?.len(),
+        stats: get_stats(conn)?,
+    })
+}
+
+/// Edit a memory's content. With `expected_version`, the edit only applies
+/// if the memory is still at that version and fails with a conflict error
+/// otherwise, so a concurrent edit isn't silently overwritten. With
+/// `refresh`, last_tapped_at is set to now as well (tap_count is untouched)
+/// and the EDIT event records `"refreshed": true`.
+pub fn edit_memory(conn: &Connection, id: &str, new_content: &str, expected_version: Option<i64>, refresh: bool) -> Result<bool> {
+    // Get old content for event log
+    let current: Option<(String, i64)> = conn.query_row(
+        "SELECT content, version FROM memories WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
     ).ok();
+    let Some((old_content, version)) = current else {
+        return Ok(false);
+    };
 
     let rows_affected = conn.execute(
-        "UPDATE memories SET content = ?1 WHERE id = ?2",
-        params![new_content, id],
+        "UPDATE memories SET content = ?1, version = version + 1,
+             last_tapped_at = CASE WHEN ?4 THEN ?5 ELSE last_tapped_at END
+         WHERE id = ?2 AND (?3 IS NULL OR version = ?3)",
+        params![new_content, id, expected_version, refresh, now_timestamp()],
     )?;
+    if rows_affected == 0 {
+        // The row exists, so only the version check can have failed
+        return Err(conflict_error(format!(
+            "version conflict on {}: expected {}, found {}",
+            id,
+            expected_version.unwrap_or_default(),
+            version
+        )));
+    }
 
-    if rows_affected > 0 {
-        let data = format!(r#"{{"old":"{}","new":"{}"}}"#,
-            old_content.unwrap_or_default().replace('\\', "\\\\").replace('"', "\\\""),
-            new_content.replace('\\', "\\\\").replace('"', "\\\""));
-        log_event(conn, "EDIT", Some(id), Some(&data))?;
+    let mut data = serde_json::json!({ "old": old_content, "new": new_content });
+    if refresh {
+        data["refreshed"] = true.into();
     }
-    Ok(rows_affected > 0)
+    log_event(conn, "EDIT", Some(id), Some(&data.to_string()))?;
+    Ok(true)
 }
 
-/// Tap a memory by ID - increments tap_count and updates last_tapped_at
-pub fn tap_memory(conn: &Connection, id: &str) -> Result<bool> {
+/// Pin or unpin a memory, logging PIN/UNPIN. Returns false if not found.
+pub fn set_pinned(conn: &Connection, id: &str, pinned: bool) -> Result<bool> {
     let rows_affected = conn.execute(
-        "UPDATE memories SET tap_count = tap_count + 1, last_tapped_at = ?1 WHERE id = ?2",
-        params![now_timestamp(), id],
+        "UPDATE memories SET pinned = ?1, version = version + 1 WHERE id = ?2",
+        params![pinned, id],
     )?;
     if rows_affected > 0 {
-        log_event(conn, "TAP", Some(id), None)?;
+        log_event(conn, if pinned { "PIN" } else { "UNPIN" }, Some(id), None)?;
     }
     Ok(rows_affected > 0)
 }
 
-/// Tap memories matching a substring - returns list of tapped IDs
-pub fn tap_memories_by_match(conn: &Connection, pattern: &str) -> Result<Vec<MemoryId>> {
-    let search = format!("%{}%", pattern);
-    let timestamp = now_timestamp();
+/// Tap a memory by ID - increments tap_count and updates last_tapped_at.
+/// Skips the increment if it was already tapped within the last
+/// `dedup_secs` seconds (0 disables the window); a skipped tap is logged
+/// as a RETAP event so it stays visible. Tap events are subject to `policy`.
+/// Forgotten memories aren't tapped; promoted ones are, so their use after
+/// promotion stays on record.
+pub fn tap_memory(conn: &Connection, id: &str, dedup_secs: u32, policy: &EventPolicy) -> Result<TapOutcome> {
+    tap_memory_weighted(conn, id, 1, dedup_secs, policy)
+}
 
-    // First get the IDs that will be tapped
-    let mut stmt = conn.prepare("SELECT id FROM memories WHERE content LIKE ?1")?;
-    let ids: Vec<String> = stmt.query_map(params![search], |row| row.get(0))?
-        .collect::<Result<Vec<_>>>()?;
+/// Event data for a tap of weight `count`; plain taps carry none
+fn tap_data(count: u32) -> Option<String> {
+    (count > 1).then(|| serde_json::json!({ "count": count }).to_string())
+}
+
+/// What a tap by ID did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapOutcome {
+    /// Counted, or logged as a RETAP inside the dedup window
+    Tapped,
+    NotFound,
+    /// The memory is forgotten; nothing was recorded
+    Forgotten,
+}
 
-    // Then update them
+/// Like `tap_memory`, but adds `count` to tap_count in one update and logs
+/// a single TAP event recording the weight
+pub fn tap_memory_weighted(conn: &Connection, id: &str, count: u32, dedup_secs: u32, policy: &EventPolicy) -> Result<TapOutcome> {
+    if count == 0 {
+        return Err(misuse_error("tap count must be at least 1".to_string()));
+    }
+    match lifecycle(conn, id)? {
+        None => return Ok(TapOutcome::NotFound),
+        Some(Lifecycle::Forgotten) => return Ok(TapOutcome::Forgotten),
+        Some(_) => {}
+    }
+    let now = now_timestamp();
+    let rows_affected = conn.execute(
+        "UPDATE memories SET tap_count = tap_count + ?4, last_tapped_at = ?1, version = version + 1
+         WHERE id = ?2
+           AND (?3 = 0 OR last_tapped_at IS NULL
+                OR datetime(last_tapped_at) <= datetime(?1, '-' || ?3 || ' seconds'))",
+        params![now, id, dedup_secs, count],
+    )?;
+    // The memory exists, so nothing updated means the tap was deduped
+    let action = if rows_affected > 0 { "TAP" } else { "RETAP" };
+    let data = if rows_affected > 0 { tap_data(count) } else { None };
+    log_event_with(conn, policy, action, Some(id), data.as_deref())?;
+    Ok(TapOutcome::Tapped)
+}
+
+/// Store a memory's embedding, replacing any previous one
+#[cfg(feature = "semantic")]
+pub fn set_embedding(conn: &Connection, id: &str, vector: &[u8]) -> Result<()> {
     conn.execute(
-        "UPDATE memories SET tap_count = tap_count + 1, last_tapped_at = ?1 WHERE content LIKE ?2",
-        params![timestamp, search],
+        "INSERT OR REPLACE INTO memory_embeddings (memory_id, vector) VALUES (?1, ?2)",
+        params![id, vector],
     )?;
+    Ok(())
+}
 
-    // Log TAP events for each matched memory
+/// All stored embeddings as (memory ID, vector blob)
+#[cfg(feature = "semantic")]
+pub fn get_embeddings(conn: &Connection) -> Result<Vec<(MemoryId, Vec<u8>)>> {
+    let mut stmt = conn.prepare("SELECT memory_id, vector FROM memory_embeddings")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// How a batch of taps by ID turned out
+#[derive(Debug, Default, PartialEq)]
+pub struct TapReport {
+    pub tapped: Vec<MemoryId>,
+    pub not_found: Vec<MemoryId>,
+    /// Forgotten memories that were left untapped
+    pub forgotten: Vec<MemoryId>,
+    /// Forgotten memories brought back before tapping
+    pub revived: Vec<MemoryId>,
+}
+
+/// Tap several memories by ID in one transaction, each `count` times.
+/// With `revive`, forgotten memories are revived and then tapped.
+pub fn tap_memories(
+    conn: &Connection,
+    ids: &[String],
+    count: u32,
+    dedup_secs: u32,
+    policy: &EventPolicy,
+    revive: bool,
+) -> Result<TapReport> {
+    let tx = conn.unchecked_transaction()?;
+    let mut report = TapReport::default();
+    for id in ids {
+        if revive && revive_memory(&tx, id)? {
+            report.revived.push(id.clone());
+        }
+        match tap_memory_weighted(&tx, id, count, dedup_secs, policy)? {
+            TapOutcome::Tapped => report.tapped.push(id.clone()),
+            TapOutcome::NotFound => report.not_found.push(id.clone()),
+            TapOutcome::Forgotten => report.forgotten.push(id.clone()),
+        }
+    }
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Tap memories matching a substring `count` times, skipping forgotten ones
+/// as `tap_memory` does - returns list of tapped IDs. With `whole_word`, the pattern must match as a whole word. A blank
+/// pattern matches every memory, so it's rejected unless `force`.
+///
+/// The match, the updates and the TAP events share one immediate
+/// transaction, so a concurrent writer can't slip in between them and a
+/// failure leaves neither counts nor events behind.
+pub fn tap_memories_by_match(
+    conn: &Connection,
+    pattern: &str,
+    count: u32,
+    whole_word: bool,
+    force: bool,
+    policy: &EventPolicy,
+) -> Result<Vec<MemoryId>> {
+    if count == 0 {
+        return Err(misuse_error("tap count must be at least 1".to_string()));
+    }
+    let blank = pattern.trim().is_empty();
+    if blank && !force {
+        return Err(misuse_error("empty match pattern would tap every memory".to_string()));
+    }
+    let search = if blank { "%".to_string() } else { format!("%{}%", escape_like(pattern)) };
+    let timestamp = now_timestamp();
+
+    // Take the write lock before reading, so the matched set can't go stale
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+    let mut stmt = tx.prepare(&format!(
+        "SELECT id, content FROM memories
+         WHERE content LIKE ?1 ESCAPE '\\' AND id NOT IN ({})",
+        forgotten_ids_sql()
+    ))?;
+    let ids: Vec<String> = stmt
+        .query_map(params![search], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(_, content)| blank || !whole_word || contains_word(content, pattern))
+        .map(|(id, _)| id)
+        .collect();
+    drop(stmt);
+
+    let data = tap_data(count);
     for id in &ids {
-        log_event(conn, "TAP", Some(id), None)?;
+        tx.execute(
+            "UPDATE memories SET tap_count = tap_count + ?3, last_tapped_at = ?1, version = version + 1
+             WHERE id = ?2",
+            params![timestamp, id, count],
+        )?;
+        log_event_with(&tx, policy, "TAP", Some(id), data.as_deref())?;
     }
+    tx.commit()?;
 
     Ok(ids)
 }
@@ -343,6 +1578,29 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_open_in_memory_path() {
+        let config = Config { db_path: PathBuf::from(":memory:"), ..Config::default() };
+        let conn = open_db(&config).unwrap();
+        let id = add_memory(&conn, "ephemeral").unwrap();
+        assert!(get_memory(&conn, &id).unwrap().is_some());
+        assert!(!std::path::Path::new(":memory:").exists());
+    }
+
+    #[test]
+    fn test_apply_pragmas() {
+        let conn = open_test_db();
+        let pragma = |k: &str, v: &str| vec![(k.to_string(), v.to_string())];
+
+        assert!(apply_pragmas(&conn, &pragma("cache_size", "-4000")).is_ok());
+        assert!(apply_pragmas(&conn, &pragma("synchronous", "NORMAL")).is_ok());
+
+        // Unknown pragmas and values SQLite refuses are reported, not ignored
+        assert!(apply_pragmas(&conn, &pragma("not_a_pragma", "1")).is_err());
+        assert!(apply_pragmas(&conn, &pragma("journal_mode", "WAL")).is_err());
+        assert!(apply_pragmas(&conn, &pragma("cache_size; DROP TABLE memories", "1")).is_err());
+    }
+
     #[test]
     fn test_add_and_get_memory() {
         let conn = open_test_db();
@@ -357,6 +1615,48 @@ mod tests {
         assert_eq!(m.content, "test content");
     }
 
+    #[test]
+    fn test_add_structured_memory() {
+        let conn = open_test_db();
+
+        let plain = add_memory(&conn, "free text").unwrap();
+        let m = get_memory(&conn, &plain).unwrap().unwrap();
+        assert_eq!(m.kind, "plain");
+        assert!(m.fields.is_none());
+
+        let fields = r#"{"problem":"flaky test","solution":"pin the seed"}"#;
+        let id = add_structured_memory(&conn, "flaky test -> pin the seed", "gotcha", Some(fields), Some("laptop/1234")).unwrap();
+        let m = get_memory(&conn, &id).unwrap().unwrap();
+        assert_eq!(m.kind, "gotcha");
+        assert_eq!(m.fields.as_deref(), Some(fields));
+        assert_eq!(m.origin.as_deref(), Some("laptop/1234"));
+    }
+
+    #[test]
+    fn test_migrate_adds_missing_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE memories (
+                id TEXT PRIMARY KEY,
+                content TEXT NOT NULL,
+                tap_count INTEGER NOT NULL DEFAULT 0,
+                last_tapped_at DATETIME,
+                created_at DATETIME NOT NULL
+            );
+            INSERT INTO memories (id, content, created_at) VALUES ('old', 'legacy row', '2024-01-01T00:00:00+00:00');"
+        ).unwrap();
+        assert!(!schema_is_current(&conn).unwrap());
+
+        init_schema(&conn).unwrap();
+        // Running again is a no-op
+        init_schema(&conn).unwrap();
+
+        assert!(schema_is_current(&conn).unwrap());
+        let m = get_memory(&conn, "old").unwrap().unwrap();
+        assert_eq!(m.kind, "plain");
+        assert!(m.fields.is_none());
+    }
+
     #[test]
     fn test_tap_memory() {
         let conn = open_test_db();
@@ -368,8 +1668,8 @@ mod tests {
         assert_eq!(m.tap_count, 0);
 
         // Tap it
-        let tapped = tap_memory(&conn, &id).expect("Failed to tap");
-        assert!(tapped);
+        let tapped = tap_memory(&conn, &id, 0, &EventPolicy::default()).expect("Failed to tap");
+        assert_eq!(tapped, TapOutcome::Tapped);
 
         // Verify tap count increased
         let m = get_memory(&conn, &id).unwrap().unwrap();
@@ -377,6 +1677,338 @@ mod tests {
         assert!(m.last_tapped_at.is_some());
     }
 
+    #[test]
+    fn test_tap_memory_dedup_window() {
+        let conn = open_test_db();
+
+        let id = add_memory(&conn, "dedup test").unwrap();
+
+        // Two taps inside the window count once
+        assert_eq!(tap_memory(&conn, &id, 60, &EventPolicy::default()).unwrap(), TapOutcome::Tapped);
+        assert_eq!(tap_memory(&conn, &id, 60, &EventPolicy::default()).unwrap(), TapOutcome::Tapped);
+        let m = get_memory(&conn, &id).unwrap().unwrap();
+        assert_eq!(m.tap_count, 1);
+
+        // The skipped tap is still recorded
+        let retaps = get_events(&conn, 10, Some("RETAP"), None, None, None, false).unwrap();
+        assert_eq!(retaps.len(), 1);
+
+        // Window disabled: every tap counts
+        assert_eq!(tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap(), TapOutcome::Tapped);
+        let m = get_memory(&conn, &id).unwrap().unwrap();
+        assert_eq!(m.tap_count, 2);
+
+        // Unknown memory is still not found
+        assert_eq!(tap_memory(&conn, "nonexistent", 60, &EventPolicy::default()).unwrap(), TapOutcome::NotFound);
+    }
+
+    #[test]
+    fn test_valid_profile_name() {
+        assert!(valid_profile_name("work"));
+        assert!(valid_profile_name("side-project_2"));
+        assert!(!valid_profile_name(""));
+        assert!(!valid_profile_name("../etc"));
+        assert!(!valid_profile_name("a/b"));
+    }
+
+    #[test]
+    fn test_contains_word() {
+        assert!(contains_word("Deploy the app", "deploy"));
+        assert!(contains_word("run make deploy.", "deploy"));
+        assert!(!contains_word("deploying now", "deploy"));
+        assert!(!contains_word("redeployment", "deploy"));
+        assert!(!contains_word("deploy_script", "deploy"));
+        assert!(contains_word("see deploying, then deploy", "deploy"));
+        assert!(contains_word("use --force here", "--force"));
+        assert!(!contains_word("anything", ""));
+    }
+
+    #[test]
+    fn test_tap_by_match_rejects_empty_pattern() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "anything").unwrap();
+
+        assert!(tap_memories_by_match(&conn, "", 1, false, false, &EventPolicy::default()).is_err());
+        assert!(tap_memories_by_match(&conn, "  ", 1, true, false, &EventPolicy::default()).is_err());
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().tap_count, 0);
+        assert!(get_events(&conn, 10, Some("TAP"), None, None, None, false).unwrap().is_empty());
+
+        // Forced, it taps every memory a pattern would: promoted ones too, forgotten ones not
+        let promoted = add_memory(&conn, "graduated").unwrap();
+        promote_memory(&conn, &promoted).unwrap();
+        let gone = add_memory(&conn, "gone").unwrap();
+        forget_memory(&conn, &gone).unwrap();
+        let mut tapped = tap_memories_by_match(&conn, " ", 1, true, true, &EventPolicy::default()).unwrap();
+        tapped.sort();
+        let mut expected = vec![id, promoted];
+        expected.sort();
+        assert_eq!(tapped, expected);
+    }
+
+    #[test]
+    fn test_tap_by_match_whole_word() {
+        let conn = open_test_db();
+        let whole = add_memory(&conn, "deploy the app").unwrap();
+        add_memory(&conn, "deploying is slow").unwrap();
+
+        assert_eq!(tap_memories_by_match(&conn, "deploy", 1, true, false, &EventPolicy::default()).unwrap(), vec![whole.clone()]);
+        assert_eq!(tap_memories_by_match(&conn, "deploy", 1, false, false, &EventPolicy::default()).unwrap().len(), 2);
+        assert_eq!(get_memory(&conn, &whole).unwrap().unwrap().tap_count, 2);
+    }
+
+    #[test]
+    fn test_tap_memory_weighted() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "used heavily").unwrap();
+
+        assert_eq!(tap_memory_weighted(&conn, &id, 3, 0, &EventPolicy::default()).unwrap(), TapOutcome::Tapped);
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().tap_count, 3);
+        assert!(tap_memory_weighted(&conn, &id, 0, 0, &EventPolicy::default()).is_err());
+
+        let taps = get_events(&conn, 10, Some("TAP"), None, None, None, false).unwrap();
+        assert_eq!(taps.len(), 1);
+        assert_eq!(taps[0].data.as_deref(), Some(r#"{"count":3}"#));
+
+        assert_eq!(tap_memories_by_match(&conn, "heavily", 2, false, false, &EventPolicy::default()).unwrap(), vec![id.clone()]);
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().tap_count, 5);
+    }
+
+    #[test]
+    fn test_migrate_profiles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let legacy = root.join("home").join(".engram").join("profiles");
+        let dir = root.join("data").join("engram").join("profiles");
+        fs::create_dir_all(legacy.join("work")).unwrap();
+        fs::write(legacy.join("work").join("engram.db"), "db").unwrap();
+
+        assert!(migrate_profiles(&legacy, &dir).unwrap());
+        assert!(dir.join("work").join("engram.db").exists());
+        assert!(!legacy.exists());
+
+        // Once migrated, a new legacy directory is left alone
+        fs::create_dir_all(&legacy).unwrap();
+        assert!(!migrate_profiles(&legacy, &dir).unwrap());
+        assert!(legacy.exists());
+
+        // A move that can't happen is an error, and the profiles stay put
+        let blocked = root.join("file").join("profiles");
+        fs::write(root.join("file"), "not a directory").unwrap();
+        let err = migrate_profiles(&legacy, &blocked).unwrap_err();
+        assert!(err.to_string().contains(&legacy.display().to_string()));
+        assert!(legacy.exists());
+    }
+
+    #[test]
+    fn test_get_memories_by_ids() {
+        let conn = open_test_db();
+        let a = add_memory(&conn, "first").unwrap();
+        let b = add_memory(&conn, "second").unwrap();
+        add_memory(&conn, "not asked for").unwrap();
+
+        let found = get_memories_by_ids(&conn, &[&a, &b, "missing", &a]).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[&a].content, "first");
+        assert_eq!(found[&b].content, "second");
+        assert!(get_memories_by_ids(&conn, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hostile_input_is_literal() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "keep me").unwrap();
+        let hostile = "'; DROP TABLE memories; --";
+
+        assert!(search_memories(&conn, hostile).unwrap().is_empty());
+        assert!(get_events(&conn, 10, Some(hostile), Some(hostile), Some(hostile), Some(hostile), false).unwrap().is_empty());
+        assert!(tap_memories_by_match(&conn, hostile, 1, false, false, &EventPolicy::default()).unwrap().is_empty());
+        let activity = get_activity_by_day(&conn, hostile, &[hostile.to_string()]).unwrap();
+        assert!(activity.iter().all(|d| d.counts == vec![0]));
+        assert_eq!(tap_memory(&conn, hostile, 0, &EventPolicy::default()).unwrap(), TapOutcome::NotFound);
+
+        // LIKE wildcards in a tap pattern don't match everything either
+        assert!(tap_memories_by_match(&conn, "%", 1, false, false, &EventPolicy::default()).unwrap().is_empty());
+
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().tap_count, 0);
+        assert_eq!(get_events(&conn, 10, None, None, None, None, false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tap_event_sampling() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "tapped often").unwrap();
+        let none = EventPolicy { tap_sample_rate: 0.0 };
+
+        // Counts stay exact; only the TAP rows are dropped
+        assert_eq!(tap_memory(&conn, &id, 0, &none).unwrap(), TapOutcome::Tapped);
+        tap_memories(&conn, std::slice::from_ref(&id), 2, 0, &none, false).unwrap();
+        tap_memories_by_match(&conn, "often", 1, false, false, &none).unwrap();
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().tap_count, 4);
+        assert!(get_events(&conn, 10, Some("TAP"), None, None, None, false).unwrap().is_empty());
+
+        // Lifecycle events are never sampled
+        forget_memory(&conn, &id).unwrap();
+        assert_eq!(get_events(&conn, 10, None, None, None, None, false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_get_events_grep() {
+        let conn = open_test_db();
+
+        add_memory(&conn, "deploy with 100% coverage").unwrap();
+        add_memory(&conn, "deploy_script lives in bin/").unwrap();
+        add_memory(&conn, "unrelated note").unwrap();
+
+        let events = get_events(&conn, 10, None, None, None, Some("deploy"), false).unwrap();
+        assert_eq!(events.len(), 2);
+
+        // Wildcards in the term are matched literally
+        let events = get_events(&conn, 10, None, None, None, Some("100%"), false).unwrap();
+        assert_eq!(events.len(), 1);
+        let events = get_events(&conn, 10, None, None, None, Some("y_s"), false).unwrap();
+        assert_eq!(events.len(), 1);
+        let events = get_events(&conn, 10, None, None, None, Some("%"), false).unwrap();
+        assert_eq!(events.len(), 1);
+
+        // Composes with the action filter
+        let events = get_events(&conn, 10, Some("TAP"), None, None, Some("deploy"), false).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_get_events_excluding_action() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "reviewed a lot").unwrap();
+        // Older stores logged a REVIEW per memory on every init
+        for _ in 0..3 {
+            log_event(&conn, "REVIEW", Some(&id), None).unwrap();
+        }
+
+        let events = get_events(&conn, 10, None, Some("REVIEW"), None, None, false).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, "ADD");
+        assert_eq!(get_events(&conn, 10, None, None, None, None, false).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_log_custom_event() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "deploys go through CI").unwrap();
+
+        log_custom_event(&conn, "DEPLOY", None, "v1.2.0").unwrap();
+        log_custom_event(&conn, "DEPLOY2", Some(&id), "canary").unwrap();
+
+        let events = get_events(&conn, 10, Some("DEPLOY"), None, None, None, false).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data.as_deref(), Some("v1.2.0"));
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let custom = vec![CUSTOM_ACTIONS.to_string()];
+        assert_eq!(get_activity_by_day(&conn, &today, &custom).unwrap()[0].counts, vec![2]);
+
+        // Lowercase, punctuation, reserved names and unknown memories are rejected
+        assert!(log_custom_event(&conn, "deploy", None, "").is_err());
+        assert!(log_custom_event(&conn, "DE-PLOY", None, "").is_err());
+        assert!(log_custom_event(&conn, "1DEPLOY", None, "").is_err());
+        assert!(log_custom_event(&conn, "FORGET", Some(&id), "").is_err());
+        assert!(log_custom_event(&conn, "DEPLOY", Some("missing"), "").is_err());
+        assert!(get_terminal_memory_ids(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tap_memories_batch() {
+        let conn = open_test_db();
+        let a = add_memory(&conn, "first").unwrap();
+        let b = add_memory(&conn, "second").unwrap();
+
+        let ids = vec![a.clone(), "missing".to_string(), b.clone()];
+        let report = tap_memories(&conn, &ids, 1, 0, &EventPolicy::default(), false).unwrap();
+        assert_eq!(report.tapped, vec![a.clone(), b]);
+        assert_eq!(report.not_found, vec!["missing".to_string()]);
+        assert_eq!(get_memory(&conn, &a).unwrap().unwrap().tap_count, 1);
+    }
+
+    #[test]
+    fn test_tap_by_match_is_atomic_under_concurrent_adds() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config { db_path: dir.path().join("engram.db"), ..Config::default() };
+        open_db(&config).unwrap();
+
+        let adder = {
+            let config = config.clone();
+            std::thread::spawn(move || {
+                let conn = open_db(&config).unwrap();
+                for i in 0..20 {
+                    add_memory(&conn, &format!("shared note {}", i)).unwrap();
+                }
+            })
+        };
+        let conn = open_db(&config).unwrap();
+        for _ in 0..20 {
+            tap_memories_by_match(&conn, "shared", 1, false, false, &EventPolicy::default()).unwrap();
+        }
+        adder.join().unwrap();
+
+        // Every counted tap has its event, however the two writers interleaved
+        for memory in list_memories_filtered(&conn, true).unwrap() {
+            let events = get_events(&conn, 100, Some("TAP"), None, Some(&memory.id), None, false).unwrap();
+            assert_eq!(memory.tap_count as usize, events.len(), "{}", memory.content);
+        }
+    }
+
+    #[test]
+    fn test_tap_skips_forgotten_memories() {
+        let conn = open_test_db();
+        let policy = EventPolicy::default();
+        let forgotten = add_memory(&conn, "stale advice").unwrap();
+        let promoted = add_memory(&conn, "stale but promoted").unwrap();
+        forget_memory(&conn, &forgotten).unwrap();
+        promote_memory(&conn, &promoted).unwrap();
+
+        assert_eq!(tap_memory(&conn, &forgotten, 0, &policy).unwrap(), TapOutcome::Forgotten);
+        assert_eq!(get_memory(&conn, &forgotten).unwrap().unwrap().tap_count, 0);
+
+        // Promoted memories keep taking taps, for the promoted-tap audit
+        assert_eq!(tap_memory(&conn, &promoted, 0, &policy).unwrap(), TapOutcome::Tapped);
+        assert_eq!(tap_memories_by_match(&conn, "stale", 1, false, false, &policy).unwrap(), vec![promoted.clone()]);
+
+        // Reviving brings a forgotten memory back; a promoted one stays promoted
+        let ids = vec![forgotten.clone(), promoted.clone()];
+        let report = tap_memories(&conn, &ids, 1, 0, &policy, true).unwrap();
+        assert_eq!(report.revived, vec![forgotten.clone()]);
+        assert_eq!(report.tapped, ids);
+        assert!(report.forgotten.is_empty());
+        assert_eq!(lifecycle(&conn, &forgotten).unwrap(), Some(Lifecycle::Active));
+        assert_eq!(lifecycle(&conn, &promoted).unwrap(), Some(Lifecycle::Promoted));
+        assert_eq!(get_terminal_memory_ids(&conn).unwrap(), vec![promoted]);
+
+        let stats = get_stats(&conn).unwrap();
+        assert_eq!((stats.active, stats.forgotten), (1, 0));
+        assert_eq!(get_stats_trend(&conn).unwrap().last().unwrap().active, 1);
+
+        // Forgetting again is terminal again
+        forget_memory(&conn, &forgotten).unwrap();
+        assert_eq!(lifecycle(&conn, &forgotten).unwrap(), Some(Lifecycle::Forgotten));
+    }
+
+    #[test]
+    fn test_set_pinned() {
+        let conn = open_test_db();
+
+        let id = add_memory(&conn, "keep forever").unwrap();
+        assert!(!get_memory(&conn, &id).unwrap().unwrap().pinned);
+
+        assert!(set_pinned(&conn, &id, true).unwrap());
+        assert!(get_memory(&conn, &id).unwrap().unwrap().pinned);
+        assert_eq!(get_events(&conn, 10, Some("PIN"), None, None, None, false).unwrap().len(), 1);
+
+        assert!(set_pinned(&conn, &id, false).unwrap());
+        assert!(!get_memory(&conn, &id).unwrap().unwrap().pinned);
+        assert_eq!(get_events(&conn, 10, Some("UNPIN"), None, None, None, false).unwrap().len(), 1);
+
+        assert!(!set_pinned(&conn, "nonexistent", true).unwrap());
+    }
+
     #[test]
     fn test_forget_memory() {
         let conn = open_test_db();
@@ -385,16 +2017,43 @@ mod tests {
 
         let forgotten = forget_memory(&conn, &id).expect("Failed to forget");
         assert!(forgotten);
+        assert!(!forget_memory(&conn, &id).unwrap());
 
         // Memory still exists (terminal state, not deleted)
         let memory = get_memory(&conn, &id).expect("Failed to get");
         assert!(memory.is_some());
 
         // But FORGET event was logged
-        let events = get_events(&conn, 10, Some("FORGET"), None).expect("Failed to get events");
+        let events = get_events(&conn, 10, Some("FORGET"), None, None, None, false).expect("Failed to get events");
         assert!(!events.is_empty());
     }
 
+    #[test]
+    fn test_forget_snapshots_memory() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "snapshot me").unwrap();
+        tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap();
+        set_pinned(&conn, &id, true).unwrap();
+        forget_memory(&conn, &id).unwrap();
+
+        // Later edits don't change what the event recorded
+        let before = get_memory(&conn, &id).unwrap().unwrap();
+        conn.execute("UPDATE memories SET content = 'edited', tap_count = 0 WHERE id = ?1", params![id]).unwrap();
+
+        let events = get_events(&conn, 1, Some("FORGET"), None, None, None, false).unwrap();
+        let snapshot: Memory = serde_json::from_str(events[0].data.as_deref().unwrap()).unwrap();
+        assert_eq!(snapshot.id, before.id);
+        assert_eq!(snapshot.content, "snapshot me");
+        assert_eq!(snapshot.tap_count, 1);
+        assert_eq!(snapshot.last_tapped_at, before.last_tapped_at);
+        assert_eq!(snapshot.created_at, before.created_at);
+        assert_eq!(snapshot.kind, before.kind);
+        assert_eq!(snapshot.fields, before.fields);
+        assert_eq!(snapshot.pinned, before.pinned);
+        assert_eq!(snapshot.version, before.version);
+        assert_eq!(snapshot.origin, before.origin);
+    }
+
     #[test]
     fn test_promote_memory() {
         let conn = open_test_db();
@@ -403,16 +2062,38 @@ mod tests {
 
         let content = promote_memory(&conn, &id).expect("Failed to promote");
         assert_eq!(content, Some("important fact".to_string()));
+        assert_eq!(promote_memory(&conn, &id).unwrap(), None);
 
         // Memory still exists
         let memory = get_memory(&conn, &id).expect("Failed to get");
         assert!(memory.is_some());
 
         // PROMOTE event was logged
-        let events = get_events(&conn, 10, Some("PROMOTE"), None).expect("Failed to get events");
+        let events = get_events(&conn, 10, Some("PROMOTE"), None, None, None, false).expect("Failed to get events");
         assert!(!events.is_empty());
     }
 
+    #[test]
+    fn test_edit_can_refresh_staleness() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "deploy with make release").unwrap();
+        tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap();
+        conn.execute("UPDATE memories SET last_tapped_at = '2020-01-01T00:00:00+00:00' WHERE id = ?1", params![id]).unwrap();
+
+        edit_memory(&conn, &id, "deploy with make ship", None, false).unwrap();
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().last_tapped_at.as_deref(), Some("2020-01-01T00:00:00+00:00"));
+
+        edit_memory(&conn, &id, "deploy with make publish", None, true).unwrap();
+        let memory = get_memory(&conn, &id).unwrap().unwrap();
+        assert!(memory.last_tapped_at.unwrap().as_str() > "2020-01-01T00:00:00+00:00");
+        assert_eq!(memory.tap_count, 1);
+
+        let events = get_events(&conn, 2, Some("EDIT"), None, None, None, false).unwrap();
+        let data = |e: &Event| serde_json::from_str::<serde_json::Value>(e.data.as_deref().unwrap()).unwrap();
+        assert_eq!(data(&events[0])["refreshed"], true);
+        assert!(data(&events[1]).get("refreshed").is_none());
+    }
+
     #[test]
     fn test_edit_memory() {
         let conn = open_test_db();
@@ -424,7 +2105,7 @@ mod tests {
         assert_eq!(m.content, "original content");
 
         // Edit it
-        let edited = edit_memory(&conn, &id, "updated content").expect("Failed to edit");
+        let edited = edit_memory(&conn, &id, "updated content", None, false).expect("Failed to edit");
         assert!(edited);
 
         // Verify update
@@ -432,10 +2113,73 @@ mod tests {
         assert_eq!(m.content, "updated content");
 
         // Edit non-existent returns false
-        let edited = edit_memory(&conn, "nonexistent", "new").expect("Failed to edit");
+        let edited = edit_memory(&conn, "nonexistent", "new", None, false).expect("Failed to edit");
         assert!(!edited);
     }
 
+    #[test]
+    fn test_forget_where() {
+        let conn = open_test_db();
+        let old_untapped = add_memory(&conn, "old untapped").unwrap();
+        let old_tapped = add_memory(&conn, "old tapped").unwrap();
+        let old_pinned = add_memory(&conn, "old pinned").unwrap();
+        tap_memory(&conn, &old_tapped, 0, &EventPolicy::default()).unwrap();
+        set_pinned(&conn, &old_pinned, true).unwrap();
+        conn.execute("UPDATE memories SET created_at = '2020-01-01T00:00:00+00:00'", []).unwrap();
+        let fresh = add_memory(&conn, "fresh untapped").unwrap();
+
+        let filter = ForgetFilter {
+            created_before: Some("2021-01-01T00:00:00+00:00".to_string()),
+            max_taps: Some(0),
+        };
+        let matched: Vec<_> = memories_where(&conn, &filter).unwrap().into_iter().map(|m| m.id).collect();
+        assert_eq!(matched, vec![old_untapped.clone()]);
+
+        let forgotten = forget_memories(&conn, &matched).unwrap();
+        assert_eq!(forgotten.len(), 1);
+        let terminal = get_terminal_memory_ids(&conn).unwrap();
+        assert_eq!(terminal, vec![old_untapped]);
+
+        // Already forgotten memories don't match again; age alone still finds the tapped one
+        assert!(memories_where(&conn, &filter).unwrap().is_empty());
+        let by_age = ForgetFilter { max_taps: None, ..filter };
+        let matched: Vec<_> = memories_where(&conn, &by_age).unwrap().into_iter().map(|m| m.id).collect();
+        assert_eq!(matched, vec![old_tapped]);
+        assert!(!matched.contains(&fresh));
+    }
+
+    #[test]
+    fn test_promote_memories_batch() {
+        let conn = open_test_db();
+        let a = add_memory(&conn, "first").unwrap();
+        let b = add_memory(&conn, "second").unwrap();
+
+        promote_memories(&conn, &[a.clone(), b.clone()]).unwrap();
+        let promoted: Vec<_> = list_promoted_memories(&conn).unwrap().into_iter().map(|m| m.id).collect();
+        assert_eq!(promoted, vec![a, b]);
+    }
+
+    #[test]
+    fn test_edit_memory_expected_version() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "original").unwrap();
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().version, 0);
+
+        tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap();
+        assert_eq!(get_memory(&conn, &id).unwrap().unwrap().version, 1);
+
+        // Stale version is rejected and nothing changes
+        assert!(edit_memory(&conn, &id, "stale", Some(0), false).is_err());
+        let m = get_memory(&conn, &id).unwrap().unwrap();
+        assert_eq!(m.content, "original");
+        assert_eq!(m.version, 1);
+
+        assert!(edit_memory(&conn, &id, "fresh", Some(1), false).unwrap());
+        let m = get_memory(&conn, &id).unwrap().unwrap();
+        assert_eq!(m.content, "fresh");
+        assert_eq!(m.version, 2);
+    }
+
     #[test]
     fn test_list_memories() {
         let conn = open_test_db();
@@ -447,6 +2191,55 @@ mod tests {
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_search_memories() {
+        let conn = open_test_db();
+
+        let id = add_memory(&conn, "Run Cargo fmt before commit").unwrap();
+        add_memory(&conn, "Use 50% of the budget").unwrap();
+        add_memory(&conn, "unrelated").unwrap();
+
+        // Case-insensitive substring match
+        let found = search_memories_filtered(&conn, "cargo", false).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, id);
+
+        // Wildcards are literal
+        assert_eq!(search_memories_filtered(&conn, "50%", false).unwrap().len(), 1);
+        assert!(search_memories_filtered(&conn, "5_%", false).unwrap().is_empty());
+
+        // Terminal memories are hidden unless requested
+        forget_memory(&conn, &id).unwrap();
+        assert!(search_memories_filtered(&conn, "cargo", false).unwrap().is_empty());
+        assert_eq!(search_memories_filtered(&conn, "cargo", true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_for_each_memory() {
+        let conn = open_test_db();
+
+        add_memory(&conn, "first").unwrap();
+        add_memory(&conn, "second").unwrap();
+        let gone = add_memory(&conn, "forgotten").unwrap();
+        forget_memory(&conn, &gone).unwrap();
+
+        let mut seen = Vec::new();
+        for_each_memory(&conn, false, |m| {
+            seen.push(m.content);
+            ControlFlow::Continue(())
+        }).unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.contains(&"forgotten".to_string()));
+
+        // Break stops after the first row
+        let mut count = 0;
+        for_each_memory(&conn, true, |_| {
+            count += 1;
+            ControlFlow::Break(())
+        }).unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[test]
     fn test_list_memories_filtered() {
         let conn = open_test_db();
@@ -468,4 +2261,214 @@ mod tests {
         let all = list_memories_filtered(&conn, true).unwrap();
         assert_eq!(all.len(), 3);
     }
+
+    #[test]
+    fn test_get_hot_memories() {
+        let conn = open_test_db();
+
+        let id1 = add_memory(&conn, "hot memory").unwrap();
+        let id2 = add_memory(&conn, "warm memory").unwrap();
+        add_memory(&conn, "cold memory").unwrap();
+        tap_memory(&conn, &id1, 0, &EventPolicy::default()).unwrap();
+        tap_memory(&conn, &id1, 0, &EventPolicy::default()).unwrap();
+        tap_memory(&conn, &id2, 0, &EventPolicy::default()).unwrap();
+
+        let since = (chrono::Local::now() - chrono::Duration::hours(1)).to_rfc3339();
+        let hot = get_hot_memories(&conn, &since).unwrap();
+        assert_eq!(hot.len(), 2);
+        assert_eq!(hot[0].memory.id, id1);
+        assert_eq!(hot[0].recent_taps, 2);
+        assert_eq!(hot[1].recent_taps, 1);
+
+        // Window that starts in the future sees nothing
+        let future = (chrono::Local::now() + chrono::Duration::hours(1)).to_rfc3339();
+        assert!(get_hot_memories(&conn, &future).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_future_events_are_ignored() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "skewed").unwrap();
+        let tomorrow = chrono::Local::now() + chrono::Duration::days(1);
+        conn.execute(
+            "INSERT INTO events (timestamp, action, memory_id) VALUES (?1, 'TAP', ?2)",
+            params![tomorrow.to_rfc3339(), id],
+        )
+        .unwrap();
+
+        let since = (chrono::Local::now() - chrono::Duration::hours(1)).to_rfc3339();
+        assert!(get_hot_memories(&conn, &since).unwrap().is_empty());
+
+        let actions = vec!["TAP".to_string()];
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let activity = get_activity_by_day(&conn, &today, &actions).unwrap();
+        assert!(activity.iter().all(|d| d.counts == vec![0]));
+
+        let health = check_health(&conn).unwrap();
+        assert_eq!(health.future_events, 1);
+        assert_eq!(health.future_memories, 0);
+    }
+
+    #[test]
+    fn test_get_activity_by_day() {
+        let conn = open_test_db();
+
+        let id = add_memory(&conn, "active").unwrap();
+        tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap();
+        tap_memory(&conn, &id, 0, &EventPolicy::default()).unwrap();
+        conn.execute(
+            "INSERT INTO events (timestamp, action, memory_id) VALUES ('2000-01-01T12:00:00+00:00', 'ADD', 'old')",
+            [],
+        ).unwrap();
+
+        let actions: Vec<String> = ["ADD", "TAP", "PROMOTE", "FORGET"].map(String::from).to_vec();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let days = get_activity_by_day(&conn, &today, &actions).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].day, today);
+        assert_eq!(days[0].counts, vec![1, 2, 0, 0]);
+
+        // Columns follow the requested order, with the custom pseudo-action anywhere
+        let chosen: Vec<String> = [CUSTOM_ACTIONS, "TAP"].map(String::from).to_vec();
+        assert_eq!(get_activity_by_day(&conn, &today, &chosen).unwrap()[0].counts, vec![0, 2]);
+
+        let all = get_activity_by_day(&conn, "1999-12-31", &actions).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].day, "2000-01-01");
+    }
+
+    #[test]
+    fn test_get_stats_trend() {
+        let conn = open_test_db();
+        let log = |day: &str, action: &str, id: &str| {
+            conn.execute(
+                "INSERT INTO events (timestamp, action, memory_id) VALUES (?1, ?2, ?3)",
+                params![format!("{}T12:00:00+00:00", day), action, id],
+            )
+            .unwrap();
+        };
+        log("2024-01-01", "ADD", "a");
+        log("2024-01-01", "ADD", "b");
+        log("2024-01-01", "TAP", "a");
+        log("2024-01-03", "ADD", "c");
+        log("2024-01-03", "PROMOTE", "a");
+        log("2024-01-04", "FORGET", "b");
+        log("2024-01-04", "FORGET", "b");
+
+        let trend: Vec<(String, u32)> = get_stats_trend(&conn).unwrap().into_iter().map(|d| (d.day, d.active)).collect();
+        assert_eq!(
+            trend,
+            vec![("2024-01-01".to_string(), 2), ("2024-01-03".to_string(), 2), ("2024-01-04".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_get_stats() {
+        let conn = open_test_db();
+
+        let id1 = add_memory(&conn, "stats one").unwrap();
+        let id2 = add_memory(&conn, "stats two").unwrap();
+        tap_memory(&conn, &id1, 0, &EventPolicy::default()).unwrap();
+        tap_memory(&conn, &id1, 0, &EventPolicy::default()).unwrap();
+        promote_memory(&conn, &id2).unwrap();
+
+        let stats = get_stats(&conn).unwrap();
+        assert_eq!(stats.total_memories, 2);
+        assert_eq!(stats.active, 1);
+        assert_eq!(stats.promoted, 1);
+        assert_eq!(stats.forgotten, 0);
+        assert_eq!(stats.total_taps, 2);
+        assert_eq!(stats.total_events, 5); // 2 ADD + 2 TAP + 1 PROMOTE
+        assert!(stats.db_size_bytes > 0);
+        assert!(stats.wal_size_bytes.is_none());
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let source = open_test_db();
+        let tapped = add_structured_memory(&source, "fix: retry", "gotcha", Some(r#"{"fix":"retry"}"#), Some("laptop/1")).unwrap();
+        let promoted = add_memory(&source, "promote me").unwrap();
+        let forgotten = add_memory(&source, "forget me").unwrap();
+        tap_memory_weighted(&source, &tapped, 3, 0, &EventPolicy::default()).unwrap();
+        set_pinned(&source, &tapped, true).unwrap();
+        promote_memory(&source, &promoted).unwrap();
+        forget_memory(&source, &forgotten).unwrap();
+        log_event(&source, "DEPLOY", None, Some("v1.2")).unwrap();
+
+        let export = export_store(&source).unwrap();
+        let json = serde_json::to_string(&export).unwrap();
+        let target = open_test_db();
+        assert_eq!(import_store(&target, &serde_json::from_str(&json).unwrap()).unwrap(), (3, 0));
+
+        let reexport = export_store(&target).unwrap();
+        assert_eq!(
+            serde_json::to_value(&export.memories).unwrap(),
+            serde_json::to_value(&reexport.memories).unwrap()
+        );
+        fn strip_ids(events: &[Event]) -> Vec<(&str, &str, Option<&str>, Option<&str>)> {
+            events
+                .iter()
+                .map(|e| (e.timestamp.as_str(), e.action.as_str(), e.memory_id.as_deref(), e.data.as_deref()))
+                .collect()
+        }
+        assert_eq!(strip_ids(&export.events), strip_ids(&reexport.events));
+        assert_eq!(lifecycle(&target, &promoted).unwrap(), Some(Lifecycle::Promoted));
+
+        // Importing again skips what's already there
+        assert_eq!(import_store(&target, &export).unwrap(), (0, 3));
+        assert_eq!(export_store(&target).unwrap().events.len(), export.events.len());
+    }
+
+    #[test]
+    fn test_check_health() {
+        let conn = open_test_db();
+        let id = add_memory(&conn, "healthy").unwrap();
+        log_event(&conn, "NOTE", Some("gone"), Some("{}")).unwrap();
+
+        let health = check_health(&conn).unwrap();
+        assert!(health.schema_current);
+        assert_eq!(health.db_path, ":memory:");
+        assert_eq!(health.orphaned_events, 1);
+        assert_eq!(health.malformed_events, 0);
+        assert_eq!(health.stats.total_memories, 1);
+        assert!(get_memory(&conn, &id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_repair_malformed_events() {
+        let conn = open_test_db();
+        add_memory(&conn, "line one\nline two").unwrap();
+        // As written by the old escaper: quotes escaped, newline left raw
+        log_event(&conn, "ADD", None, Some("{\"content\":\"say \\\"hi\\\"\nthen go\"}")).unwrap();
+        conn.execute(
+            "INSERT INTO events (timestamp, action, data) VALUES ('2024-01-01T00:00:00Z', 'TAP', CAST(X'6F6BFF' AS TEXT))",
+            [],
+        )
+        .unwrap();
+
+        let malformed = malformed_events(&conn).unwrap();
+        assert_eq!(malformed.len(), 2);
+        assert_eq!(repair_events(&conn).unwrap(), 2);
+        assert!(malformed_events(&conn).unwrap().is_empty());
+
+        let events = get_events(&conn, 10, None, None, None, None, false).unwrap();
+        let data: Vec<serde_json::Value> = events
+            .iter()
+            .map(|e| serde_json::from_str(e.data.as_deref().unwrap()).unwrap())
+            .collect();
+        assert_eq!(data[0], serde_json::json!("ok\u{fffd}"));
+        assert_eq!(data[1]["content"], "say \"hi\"\nthen go");
+        assert_eq!(data[2]["content"], "line one\nline two");
+    }
+
+    #[test]
+    fn test_repair_leaves_custom_events_alone() {
+        let conn = open_test_db();
+        log_custom_event(&conn, "DEPLOY", None, "v1.2").unwrap();
+
+        assert!(malformed_events(&conn).unwrap().is_empty());
+        assert_eq!(repair_events(&conn).unwrap(), 0);
+        let events = get_events(&conn, 1, Some("DEPLOY"), None, None, None, false).unwrap();
+        assert_eq!(events[0].data.as_deref(), Some("v1.2"));
+    }
 }
@@ -0,0 +1,280 @@
+//! Display helpers shared by the CLI (main.rs) and the TUI (tui.rs)
+//!
+//! Stored content is never altered; these only shape it for the terminal.
+
+use crossterm::style::Stylize;
+
+/// Default width of a one-line memory preview (overridden by `--truncate`)
+pub const MEMORY_WIDTH: usize = 60;
+
+/// Default width of event content in `log` (overridden by `--truncate`)
+pub const EVENT_WIDTH: usize = 50;
+
+/// Width of event content in the TUI events panel, which gets half the screen
+pub const EVENT_PANEL_WIDTH: usize = 40;
+
+/// Width of a staged action's preview in the TUI pending list
+pub const PENDING_WIDTH: usize = 30;
+
+/// Escape control characters so content renders on one line.
+/// Newlines and tabs become `\n`/`\t`; other control chars become `\u{..}`.
+pub fn sanitize(s: &str) -> String {
+    escape_control(s, false)
+}
+
+/// Escape control characters but keep newlines and tabs, for multi-line views
+pub fn sanitize_block(s: &str) -> String {
+    escape_control(s, true)
+}
+
+fn escape_control(s: &str, keep_layout: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' | '\t' if keep_layout => out.push(c),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Sanitize and cut to at most `max_len` characters, ending in "..." if cut.
+/// A `max_len` of 0 means no limit.
+pub fn truncate(s: &str, max_len: usize) -> String {
+    let clean = sanitize(s);
+    if max_len == 0 || clean.chars().count() <= max_len {
+        clean
+    } else {
+        let kept: String = clean.chars().take(max_len.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    }
+}
+
+/// Sanitize a multi-line block and mark each line with a gutter, so code
+/// stands apart from surrounding prose
+pub fn frame_code(s: &str) -> String {
+    sanitize_block(s)
+        .lines()
+        .map(|line| format!("│ {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Columns to wrap to: `requested` if given, else the terminal's width,
+/// else 80 when stdout isn't a terminal
+pub fn wrap_width(requested: Option<usize>) -> usize {
+    use std::io::IsTerminal;
+
+    requested
+        .or_else(|| {
+            std::io::stdout()
+                .is_terminal()
+                .then(|| crossterm::terminal::size().ok())
+                .flatten()
+                .map(|(cols, _)| cols as usize)
+        })
+        .unwrap_or(80)
+}
+
+/// Word-wrap text that will be printed after `indent` columns of label, so
+/// no line passes `width`. Continuation lines are indented to line up with
+/// the first; words longer than a line are split.
+pub fn wrap(s: &str, width: usize, indent: usize) -> String {
+    let avail = width.saturating_sub(indent).max(10);
+    let mut lines = Vec::new();
+    for paragraph in s.lines() {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            if len > 0 && len + 1 + word.len() > avail {
+                lines.push(std::mem::take(&mut line));
+                len = 0;
+            }
+            if len == 0 {
+                while word.len() > avail {
+                    lines.push(word.drain(..avail).collect());
+                }
+            } else {
+                line.push(' ');
+                len += 1;
+            }
+            line.extend(&word);
+            len += word.len();
+        }
+        lines.push(line);
+    }
+    lines.join(&format!("\n{}", " ".repeat(indent)))
+}
+
+/// Extract the time portion of an RFC3339 timestamp
+pub fn format_timestamp(ts: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|_| "Invalid".to_string())
+}
+
+/// Format a byte count for humans (e.g. "1.5 MiB")
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Render values as a row of unicode block characters scaled to the max.
+/// Zero is the lowest block; any non-zero value shows at least one step up.
+pub fn sparkline(values: &[u64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            if v == 0 || max == 0 {
+                BLOCKS[0]
+            } else {
+                let level = (v * 7).div_ceil(max) as usize;
+                BLOCKS[level.clamp(1, 7)]
+            }
+        })
+        .collect()
+}
+
+/// A stable `<action>\t<id>\t<status>` line for `--porcelain` output
+pub fn porcelain(action: &str, id: &str, status: &str) -> String {
+    format!("{}\t{}\t{}", action, sanitize(id), status)
+}
+
+/// Highlight every case-insensitive occurrence of `pattern` in `content`
+pub fn highlight_matches(content: &str, pattern: &str, enabled: bool) -> String {
+    if !enabled || pattern.is_empty() {
+        return content.to_string();
+    }
+
+    // ASCII lowercasing keeps byte offsets aligned with the original
+    let haystack = content.to_ascii_lowercase();
+    let needle = pattern.to_ascii_lowercase();
+
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, _) in haystack.match_indices(&needle) {
+        let end = start + needle.len();
+        out.push_str(&content[last..start]);
+        out.push_str(&content[start..end].reverse().bold().to_string());
+        last = end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// Color for an event action, so lifecycle changes stand out from routine
+/// taps: adds cyan, promotes and revives green, forgets and expiries red,
+/// reviews and retaps dimmed. Other actions stay plain.
+pub fn action_color(action: &str) -> Option<crossterm::style::Color> {
+    use crossterm::style::Color;
+
+    match action {
+        "ADD" => Some(Color::Cyan),
+        "PROMOTE" | "REVIVE" => Some(Color::Green),
+        "FORGET" | "EXPIRE" => Some(Color::Red),
+        "REVIEW" | "RETAP" => Some(Color::DarkGrey),
+        _ => None,
+    }
+}
+
+/// Pad an action to `width` columns, colored by `action_color` when enabled
+pub fn action_label(action: &str, width: usize, enabled: bool) -> String {
+    let padded = format!("{:width$}", action, width = width);
+    match action_color(action) {
+        Some(color) if enabled => padded.with(color).to_string(),
+        _ => padded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_label() {
+        assert_eq!(action_label("TAP", 8, true), "TAP     ");
+        assert_eq!(action_label("EXPIRE", 8, false), "EXPIRE  ");
+        let colored = action_label("EXPIRE", 8, true);
+        assert!(colored.starts_with('\x1b') && colored.contains("EXPIRE  "));
+        assert!(action_label("DEPLOY", 8, true).starts_with("DEPLOY"));
+    }
+
+    #[test]
+    fn test_sanitize_escapes_control_chars() {
+        assert_eq!(sanitize("a\nb\tc"), "a\\nb\\tc");
+        assert_eq!(sanitize("bell\x07"), "bell\\u{7}");
+        assert_eq!(sanitize("\x1b[31mred"), "\\u{1b}[31mred");
+        // Backslashes in code snippets are left alone
+        assert_eq!(sanitize(r"C:\path"), r"C:\path");
+    }
+
+    #[test]
+    fn test_sanitize_block_keeps_layout() {
+        assert_eq!(sanitize_block("line1\n\tline2\x1b"), "line1\n\tline2\\u{1b}");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("short", 80, 4), "short");
+        assert_eq!(wrap("aaa bbb ccc ddd", 16, 4), "aaa bbb ccc\n    ddd");
+        // Existing newlines are kept and indented too
+        assert_eq!(wrap("one\ntwo", 80, 2), "one\n  two");
+        // Long words are split rather than overflowing
+        assert_eq!(wrap("abcdefghijklmnop", 12, 2), "abcdefghij\n  klmnop");
+    }
+
+    #[test]
+    fn test_porcelain() {
+        assert_eq!(porcelain("tap", "abc123", "ok"), "tap\tabc123\tok");
+        // A stray tab in a user-supplied id can't add a field
+        assert_eq!(porcelain("forget", "a\tb", "not-found"), "forget\ta\\tb\tnot-found");
+    }
+
+    #[test]
+    fn test_frame_code() {
+        assert_eq!(frame_code("fn a() {\n}\x1b"), "│ fn a() {\n│ }\\u{1b}");
+    }
+
+    #[test]
+    fn test_truncate_counts_chars() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdefghij", 8), "abcde...");
+        // Multi-byte characters don't split mid-codepoint
+        assert_eq!(truncate("ééééé", 4), "é...");
+        assert_eq!(truncate("no\nlimit", 0), "no\\nlimit");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0, 0]), "▁▁▁");
+        assert_eq!(sparkline(&[0, 1, 2, 4, 8]), "▁▂▃▅█");
+        // Small non-zero values stay visible next to a large max
+        assert_eq!(sparkline(&[1, 100]), "▂█");
+    }
+
+    #[test]
+    fn test_highlight_matches_all_occurrences() {
+        assert_eq!(highlight_matches("a b a", "a", false), "a b a");
+        let out = highlight_matches("Deploy then deploy", "deploy", true);
+        assert_eq!(out.matches("\x1b[7m").count(), 2);
+        assert!(out.contains("Deploy"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Embedding vectors for semantic search (`semantic` feature)
+//!
+//! Engram doesn't ship a model. Vectors come from a user-configured command
+//! (ENGRAM_EMBED_CMD) that reads text on stdin and prints a JSON array of
+//! floats, so any local model or HTTP endpoint can be wrapped in a script.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run the embedding command on `text` and parse the vector it prints
+pub fn embed(command: &str, text: &str) -> Result<Vec<f32>, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run embedding command: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("failed to write to embedding command: {}", e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("embedding command failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("embedding command exited with {}", output.status));
+    }
+
+    let vector: Vec<f32> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("embedding command printed invalid JSON: {}", e))?;
+    if vector.is_empty() {
+        return Err("embedding command printed an empty vector".to_string());
+    }
+    Ok(vector)
+}
+
+/// Cosine similarity; 0 for mismatched lengths or zero vectors
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Encode a vector as little-endian f32 bytes for storage
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// Decode a vector stored by `to_blob`
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_round_trip() {
+        let v = vec![0.5, -1.25, 3.0];
+        assert_eq!(from_blob(&to_blob(&v)), v);
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_embed_runs_command() {
+        assert_eq!(embed("cat >/dev/null; echo '[1, 2.5]'", "text").unwrap(), vec![1.0, 2.5]);
+        assert!(embed("echo 'not json'", "text").is_err());
+        assert!(embed("exit 3", "text").is_err());
+    }
+}
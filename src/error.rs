@@ -0,0 +1,79 @@
+//! Errors surfaced by the service layer
+//!
+//! Raw SQLite errors ("database is locked", "UNIQUE constraint failed") say
+//! what went wrong inside SQLite, not what to do about it. Common cases are
+//! mapped to actionable messages; anything else keeps the SQLite error.
+
+use rusqlite::ffi;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EngramError {
+    #[error("the store is busy: another engram process is writing, retry in a moment")]
+    Locked,
+    #[error("the store is read-only: check the database file's permissions")]
+    ReadOnly,
+    #[error("can't open the database: check ENGRAM_DB_PATH and that its directory exists")]
+    CannotOpen,
+    #[error("the database file is corrupt or isn't an engram store")]
+    Corrupt,
+    #[error("a memory with this content already exists")]
+    Duplicate,
+    #[error("{0}; re-read the memory and retry")]
+    Conflict(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("database error: {0}")]
+    Db(#[source] rusqlite::Error),
+    #[error("file error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, EngramError>;
+
+impl From<rusqlite::Error> for EngramError {
+    fn from(e: rusqlite::Error) -> Self {
+        if let rusqlite::Error::SqliteFailure(err, ref message) = e {
+            match (err.code, err.extended_code) {
+                (ffi::ErrorCode::DatabaseBusy | ffi::ErrorCode::DatabaseLocked, _) => return EngramError::Locked,
+                (ffi::ErrorCode::ReadOnly, _) => return EngramError::ReadOnly,
+                (ffi::ErrorCode::CannotOpen, _) => return EngramError::CannotOpen,
+                (ffi::ErrorCode::DatabaseCorrupt | ffi::ErrorCode::NotADatabase, _) => return EngramError::Corrupt,
+                (_, ffi::SQLITE_CONSTRAINT_PRIMARYKEY | ffi::SQLITE_CONSTRAINT_UNIQUE) => {
+                    return EngramError::Duplicate
+                }
+                // db.rs raises these itself with a message explaining the problem
+                (_, ffi::SQLITE_CONSTRAINT) if message.is_some() => {
+                    return EngramError::Conflict(message.clone().unwrap_or_default())
+                }
+                (_, ffi::SQLITE_MISUSE) if message.is_some() => {
+                    return EngramError::InvalidInput(message.clone().unwrap_or_default())
+                }
+                _ => {}
+            }
+        }
+        EngramError::Db(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite_error(code: std::os::raw::c_int, message: Option<&str>) -> rusqlite::Error {
+        rusqlite::Error::SqliteFailure(ffi::Error::new(code), message.map(String::from))
+    }
+
+    #[test]
+    fn test_maps_common_sqlite_errors() {
+        assert!(matches!(EngramError::from(sqlite_error(ffi::SQLITE_BUSY, None)), EngramError::Locked));
+        assert!(matches!(
+            EngramError::from(sqlite_error(ffi::SQLITE_CONSTRAINT_PRIMARYKEY, Some("UNIQUE constraint failed"))),
+            EngramError::Duplicate
+        ));
+        assert!(matches!(
+            EngramError::from(sqlite_error(ffi::SQLITE_MISUSE, Some("unknown pragma 'x'"))),
+            EngramError::InvalidInput(m) if m == "unknown pragma 'x'"
+        ));
+        assert!(matches!(EngramError::from(rusqlite::Error::QueryReturnedNoRows), EngramError::Db(_)));
+    }
+}
@@ -0,0 +1,47 @@
+//! User commands run after a memory is promoted or forgotten
+//!
+//! Hooks are shell commands from ENGRAM_ON_PROMOTE / ENGRAM_ON_FORGET. Each
+//! runs once per affected memory with the ID, content and tap count as
+//! `$1`, `$2` and `$3`, so a script can forward them anywhere.
+
+use std::process::{Command, Stdio};
+
+/// Run a hook for one memory. Output is captured so hooks can't disturb
+/// the terminal; a non-zero exit is returned as an error with its stderr.
+pub fn run(command: &str, id: &str, content: &str, tap_count: u32) -> Result<(), String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("engram")
+        .arg(id)
+        .arg(content)
+        .arg(tap_count.to_string())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run hook: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.trim() {
+        "" => Err(format!("hook exited with {}", output.status)),
+        message => Err(format!("hook exited with {}: {}", output.status, message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_gets_memory_as_arguments() {
+        assert!(run("test \"$1|$2|$3\" = 'abc|some text|4'", "abc", "some text", 4).is_ok());
+        assert!(run("true", "abc", "", 0).is_ok());
+    }
+
+    #[test]
+    fn test_hook_failure_reports_stderr() {
+        let err = run("echo boom >&2; exit 3", "abc", "x", 0).unwrap_err();
+        assert!(err.contains("boom"), "{}", err);
+    }
+}
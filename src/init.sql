@@ -3,7 +3,12 @@ CREATE TABLE IF NOT EXISTS memories (
     content TEXT NOT NULL,
     tap_count INTEGER NOT NULL DEFAULT 0,
     last_tapped_at DATETIME,
-    created_at DATETIME NOT NULL
+    created_at DATETIME NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'plain',
+    fields TEXT,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    version INTEGER NOT NULL DEFAULT 0,
+    origin TEXT
 );
 
 CREATE TABLE IF NOT EXISTS events (
@@ -14,6 +19,12 @@ CREATE TABLE IF NOT EXISTS events (
     data TEXT
 );
 
+-- Embedding vectors for semantic search, as little-endian f32 blobs
+CREATE TABLE IF NOT EXISTS memory_embeddings (
+    memory_id TEXT PRIMARY KEY,
+    vector BLOB NOT NULL
+);
+
 CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);
 CREATE INDEX IF NOT EXISTS idx_events_action ON events(action);
 CREATE INDEX IF NOT EXISTS idx_events_memory_id ON events(memory_id);
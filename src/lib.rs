@@ -0,0 +1,32 @@
+//! Engram - memory observability for Claude agents
+//!
+//! Embed the memory store in another tool through the [`Engram`] service:
+//!
+//! ```no_run
+//! use engram::{Config, Engram};
+//!
+//! let engram = Engram::new(&Config::from_env())?;
+//! let id = engram.add_memory("Tests run with cargo nextest")?;
+//! engram.tap_memory(&id)?;
+//! # Ok::<(), engram::EngramError>(())
+//! ```
+//!
+//! Database access stays private; everything goes through the service layer.
+
+mod claude_md;
+mod content_kind;
+mod db;
+#[cfg(feature = "semantic")]
+mod embeddings;
+mod engram;
+mod error;
+mod hooks;
+mod secrets;
+mod similarity;
+
+pub use engram::{
+    health_score, last_tapped, list_profiles, migrate_legacy_profiles, origin, origin_matches, parse_fields, render_fields, valid_profile_name, BatchAdd, Config, DayActivity, DayPopulation, Engram, EnrichedEvent, EventPolicy, ForgetFilter, Health,
+    HealthScore, HotComparison, HotMemory, Lifecycle, Memory, MemoryStats, Profile, PromoteResult, RecentMemory, RecentReason, StoreExport, TapOutcome, TapReport, Trend,
+};
+pub use error::{EngramError, Result};
+pub use secrets::detect_secret;
@@ -1,61 +1,269 @@
-use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
 
-mod db;
-mod engram;
+use clap::{Parser, Subcommand, ValueEnum};
+
+mod display;
 mod tui;
 
+use display::{
+    action_label, format_bytes, frame_code, highlight_matches, porcelain, sanitize, sanitize_block, sparkline, truncate, wrap,
+    wrap_width, EVENT_WIDTH, MEMORY_WIDTH,
+};
 use engram::Engram;
 
 #[derive(Parser)]
 #[command(name = "engram")]
 #[command(about = "Memory observability for Claude agents")]
 struct Cli {
+    /// When to use colors and highlighting
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    color: ColorMode,
+
+    /// Memory store to use, kept in <data dir>/engram/profiles/<name>/ (default:
+    /// ENGRAM_PROFILE, else "global", the legacy .engram/engram.db)
+    #[arg(long, global = true, value_parser = parse_profile)]
+    profile: Option<String>,
+
+    /// Report mutations as stable `<action>\t<id>\t<status>` lines for
+    /// scripts (add, edit, forget, promote, pin, unpin, tap)
+    #[arg(long, global = true)]
+    porcelain: bool,
+
+    /// Cut content in list, log and bulk forget/promote output to this many
+    /// characters; 0 shows it in full (default: ENGRAM_TRUNCATE, else each
+    /// command's own width)
+    #[arg(long, global = true, value_name = "N")]
+    truncate: Option<usize>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Orders for `list`
+#[derive(Clone, Copy, ValueEnum)]
+enum ListSort {
+    /// Most tapped first
+    Taps,
+    /// Newest first
+    Created,
+    /// Most recently tapped first; never-tapped last
+    #[value(alias = "last_tapped")]
+    LastTapped,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Resolve to on/off; auto means stdout is a terminal and NO_COLOR is unset
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new memory
     Add {
-        /// The memory content
-        content: String,
+        /// The memory content, or `-` to read one memory per line from stdin.
+        /// Optional with --field, where it defaults to a summary of the fields.
+        #[arg(required_unless_present = "fields")]
+        content: Option<String>,
+        /// Template kind for structured memories (e.g. gotcha)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Structured field as key=value (repeatable)
+        #[arg(long = "field", value_parser = parse_field)]
+        fields: Vec<(String, String)>,
+        /// Allow reading more than the stdin safety limit
+        #[arg(long)]
+        force: bool,
+        /// Store content even if it looks like it contains a secret
+        #[arg(long)]
+        allow_secrets: bool,
+        /// Refuse content at least this similar (0.0-1.0) to an active
+        /// memory, e.g. 0.8, and suggest tapping that one instead
+        #[arg(long, value_parser = parse_threshold)]
+        dedup_threshold: Option<f64>,
+        /// Add a large batch from stdin without stopping to show a summary
+        #[arg(long, short)]
+        yes: bool,
     },
     /// List memories
     List {
         /// Include promoted/forgotten memories
         #[arg(long, short)]
         all: bool,
+        /// Only show memories of this kind (e.g. plain, gotcha)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only show memories added on this host (or exact hostname/session origin)
+        #[arg(long)]
+        origin: Option<String>,
+        /// Only show memories whose content looks like this (url, shell, code, prose)
+        #[arg(long, value_parser = ["url", "shell", "code", "prose"])]
+        content_kind: Option<String>,
+        /// Only show memories that were never tapped
+        #[arg(long, conflicts_with = "tapped")]
+        untapped: bool,
+        /// Only show memories tapped at least once
+        #[arg(long)]
+        tapped: bool,
+        /// Only show memories not tapped within this long (e.g. 30d), or never
+        #[arg(long, value_parser = parse_age)]
+        stale: Option<chrono::Duration>,
+        /// Order of the listing
+        #[arg(long, value_enum, default_value_t = ListSort::Taps)]
+        sort: ListSort,
+        /// Stream one JSON object per line (NDJSON), in the default order
+        #[arg(long, conflicts_with = "sort")]
+        json_lines: bool,
+        /// Show full content, word-wrapped, under each memory's header
+        #[arg(long, short, conflicts_with = "json_lines")]
+        long: bool,
+        /// Wrap --long content to this many columns (default: terminal width)
+        #[arg(long, requires = "long")]
+        width: Option<usize>,
+        /// Show at most this many memories, then say how many were left out
+        #[arg(long, short = 'n', conflicts_with = "json_lines")]
+        limit: Option<usize>,
+    },
+    /// Search memories by content
+    Search {
+        /// Text to search for (case-insensitive)
+        pattern: String,
+        /// Include promoted/forgotten memories
+        #[arg(long, short)]
+        all: bool,
+        /// Rank by meaning using ENGRAM_EMBED_CMD (needs the `semantic` feature;
+        /// falls back to substring search otherwise)
+        #[arg(long)]
+        semantic: bool,
+        /// Match the pattern as a whole word, not inside longer words
+        #[arg(long, short, conflicts_with = "semantic")]
+        whole_word: bool,
+        /// Show at most this many matches, then say how many were left out
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
     },
     /// Show a specific memory
     Show {
         /// Memory ID
         id: String,
+        /// Print only the content, byte for byte with no added newline, for piping
+        #[arg(long)]
+        raw: bool,
+        /// Wrap content to this many columns (default: terminal width)
+        #[arg(long, conflicts_with = "raw")]
+        width: Option<usize>,
     },
     /// Edit a memory's content
     Edit {
         /// Memory ID
         id: String,
-        /// New content
-        content: String,
+        /// New content, or `-` to read it from stdin
+        #[arg(required_unless_present = "edit")]
+        content: Option<String>,
+        /// Open $VISUAL or $EDITOR on the current content instead
+        #[arg(long, short, conflicts_with = "content")]
+        edit: bool,
+        /// Only apply the edit if the memory is still at this version
+        #[arg(long)]
+        expect_version: Option<i64>,
     },
-    /// Forget a memory (mark as discarded)
+    /// Forget a memory (mark as discarded), or every memory matching filters
     Forget {
         /// Memory ID
-        id: String,
+        #[arg(required_unless_present_any = ["older_than", "untapped", "match_str"])]
+        id: Option<String>,
+        /// Forget every active memory whose content contains this text
+        #[arg(long = "match", conflicts_with_all = ["id", "older_than", "untapped"])]
+        match_str: Option<String>,
+        /// Only memories created longer ago than this, e.g. 30d, 12h, 2w
+        #[arg(long, value_parser = parse_age, conflicts_with = "id")]
+        older_than: Option<chrono::Duration>,
+        /// Only memories that were never tapped
+        #[arg(long, conflicts_with = "id")]
+        untapped: bool,
+        /// Print what would be forgotten without changing anything
+        #[arg(long, conflicts_with = "id")]
+        dry_run: bool,
+        /// Ask keep/pin/forget/promote/skip for each memory the filters select
+        #[arg(long, short, conflicts_with_all = ["id", "match_str", "dry_run"])]
+        interactive: bool,
+        /// Don't ask for confirmation before a bulk forget
+        #[arg(long, short, conflicts_with = "id")]
+        yes: bool,
+        /// With --match, include pinned memories
+        #[arg(long, requires = "match_str")]
+        include_pinned: bool,
     },
     /// Promote a memory to CLAUDE.md
     Promote {
+        /// Memory ID
+        #[arg(required_unless_present = "match_str")]
+        id: Option<String>,
+        /// Promote every active memory whose content contains this text
+        #[arg(long = "match", conflicts_with = "id")]
+        match_str: Option<String>,
+        /// Print what would be promoted without changing anything
+        #[arg(long, requires = "match_str")]
+        dry_run: bool,
+        /// Don't ask for confirmation with --match
+        #[arg(long, short, requires = "match_str")]
+        yes: bool,
+        /// With --match, include pinned memories
+        #[arg(long, requires = "match_str")]
+        include_pinned: bool,
+    },
+    /// Pin a memory so it's never changed automatically
+    Pin {
+        /// Memory ID
+        id: String,
+    },
+    /// Unpin a memory
+    Unpin {
         /// Memory ID
         id: String,
     },
     /// Record memory usage (tap)
     Tap {
-        /// Memory IDs to tap
+        /// Memory IDs to tap, or "-" to read newline-separated IDs from stdin
         ids: Vec<String>,
         /// Match memories by substring
         #[arg(long = "match")]
         match_str: Option<String>,
+        /// With --match, match the pattern as a whole word
+        #[arg(long, short, requires = "match_str")]
+        whole_word: bool,
+        /// Allow an empty --match pattern, tapping every memory a pattern could
+        /// match (active and promoted)
+        #[arg(long, requires = "match_str")]
+        force: bool,
+        /// Add this many taps at once, for memories that were used heavily
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+        /// Bring forgotten memories back to active instead of skipping them
+        #[arg(long, conflicts_with = "match_str")]
+        revive: bool,
+    },
+    /// Record a custom event, e.g. a DEPLOY marker, on the timeline
+    Note {
+        /// Uppercase action name (letters and digits)
+        action: String,
+        /// Free-text event data
+        data: String,
+        /// Attach the event to this memory
+        #[arg(long)]
+        id: Option<String>,
     },
     /// View event log
     Log {
@@ -68,30 +276,484 @@ enum Commands {
         /// Filter by memory ID
         #[arg(long)]
         memory: Option<String>,
+        /// Filter to events whose data contains this text
+        #[arg(long)]
+        grep: Option<String>,
         /// Include TAP events for promoted memories
         #[arg(long)]
         all: bool,
+        /// Hide REVIEW events, which older versions logged on every init
+        #[arg(long, conflicts_with = "action")]
+        no_reviews: bool,
+        /// Print one JSON object per event (NDJSON) with raw data and content
+        #[arg(long, alias = "json")]
+        json_lines: bool,
+    },
+    /// Show memories tapped recently
+    Hot {
+        /// Window in hours (default: ENGRAM_HOT_HOURS or 24)
+        #[arg(long)]
+        hours: Option<u32>,
+        /// Compare against a second, longer window in hours (e.g. 168)
+        #[arg(long)]
+        compare: Option<u32>,
+    },
+    /// Show memories added or tapped lately, newest activity first
+    Recent {
+        /// Window in hours (default: ENGRAM_HOT_HOURS or 24)
+        #[arg(long)]
+        hours: Option<u32>,
+        /// Show at most this many memories, then say how many were left out
+        #[arg(long, short = 'n', default_value = "20")]
+        limit: usize,
+    },
+    /// Show daily activity
+    Activity {
+        /// Number of days to show, including today
+        #[arg(long, short, default_value = "14")]
+        days: u32,
+        /// Render one compact sparkline row per action instead of a table
+        #[arg(long)]
+        sparkline: bool,
+        /// Comma-separated actions to show as columns; CUSTOM counts all custom events
+        #[arg(long, value_delimiter = ',', default_value = "ADD,TAP,PROMOTE,FORGET,CUSTOM", value_parser = parse_action)]
+        actions: Vec<String>,
+    },
+    /// Show store statistics
+    Stats {
+        /// Instead, show active memories per day over this many days
+        #[arg(long, value_name = "DAYS")]
+        trend: Option<u32>,
+    },
+    /// Print store metrics in the Prometheus text format, for scraping
+    Metrics,
+    /// List memory stores selectable with --profile
+    Profiles,
+    /// Check the store's health; only --fix-events writes anything
+    Doctor {
+        /// Re-escape event data that isn't valid JSON
+        #[arg(long)]
+        fix_events: bool,
+        /// Print the report as JSON
+        #[arg(long, conflicts_with = "fix_events")]
+        json: bool,
+    },
+    /// Write every memory and event, with all metadata, as JSON
+    Export {
+        /// File to write (default: stdout)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Load memories and their history from an `engram export` file
+    Import {
+        /// Export file, or "-" for stdin
+        path: std::path::PathBuf,
+    },
+    /// Rewrite the engram-managed block of promoted memories in CLAUDE.md
+    Sync {
+        /// File to update (default: ENGRAM_CLAUDE_MD or ./CLAUDE.md)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
     },
     /// Initialize engram for this project
-    Init,
+    Init {
+        /// Also print a selection of memories fitting in this many characters
+        #[arg(long)]
+        budget: Option<usize>,
+    },
     /// Output agent instructions for context recovery
     Prime,
     /// Launch interactive TUI
     Ui,
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
+/// Parse a `key=value` structured field
+fn parse_field(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_string(), v.to_string())),
+        _ => Err(format!("expected key=value, got '{}'", s)),
+    }
+}
+
+/// Parse an age like "30d", "12h" or "2w"
+fn parse_age(s: &str) -> Result<chrono::Duration, String> {
+    let unit_len = s.chars().last().map_or(0, char::len_utf8);
+    let (num, unit) = s.split_at(s.len() - unit_len);
+    let n: i64 = num.parse().map_err(|_| format!("expected an age like 30d, got '{}'", s))?;
+    if n <= 0 {
+        return Err(format!("age must be positive, got '{}'", s));
+    }
+    match unit {
+        "h" => Ok(chrono::Duration::hours(n)),
+        "d" => Ok(chrono::Duration::days(n)),
+        "w" => Ok(chrono::Duration::weeks(n)),
+        _ => Err(format!("unknown age unit in '{}' (use h, d or w)", s)),
+    }
+}
+
+/// Parse a similarity threshold between 0.0 and 1.0
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(t) if (0.0..=1.0).contains(&t) => Ok(t),
+        _ => Err(format!("expected a threshold between 0.0 and 1.0, got '{}'", s)),
+    }
+}
+
+/// Accept a profile name that is safe to use as a directory name
+fn parse_profile(s: &str) -> Result<String, String> {
+    if engram::valid_profile_name(s) {
+        Ok(s.to_string())
     } else {
-        format!("{}...", &s[..max_len - 3])
+        Err(format!("invalid profile '{}': use letters, digits, '-' and '_'", s))
+    }
+}
+
+/// Normalize an event action name given on the command line
+fn parse_action(s: &str) -> Result<String, String> {
+    let action = s.trim().to_ascii_uppercase();
+    if !action.is_empty() && action.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(action)
+    } else {
+        Err(format!("invalid action '{}'", s))
+    }
+}
+
+/// Ask a yes/no question on stderr. Without a terminal to ask on, the
+/// answer is no, so scripts must pass --yes explicitly.
+fn confirm(prompt: &str) -> bool {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("{} Refusing without a terminal; pass --yes to confirm.", prompt);
+        return false;
+    }
+    eprint!("{} [y/N] ", prompt);
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+/// Walk through forget candidates one at a time, applying the answer to each.
+/// Keeping a memory pins it so later sweeps don't ask again.
+fn review_candidates(engram: &Engram, candidates: &[engram::Memory]) {
+    let (mut forgotten, mut kept, mut pinned, mut promoted) = (0, 0, 0, 0);
+    for (i, m) in candidates.iter().enumerate() {
+        println!();
+        println!("({}/{}) [{}] taps:{} created:{}", i + 1, candidates.len(), m.id, m.tap_count, m.created_at);
+        println!("{}", sanitize_block(&m.content));
+        let result = match ask_review() {
+            'f' => engram.forget_memory(&m.id).map(|_| forgotten += 1),
+            // Keeping leaves the memory for the next sweep to ask about
+            // again; pinning exempts it from sweeps for good
+            'k' => {
+                kept += 1;
+                Ok(())
+            }
+            'n' => engram.pin_memory(&m.id).map(|_| pinned += 1),
+            'p' => engram.promote_memory(&m.id).map(|result| {
+                promoted += 1;
+                report_sync(&result.synced);
+            }),
+            's' => Ok(()),
+            _ => break,
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to update memory: {}", e);
+            std::process::exit(1);
+        }
+    }
+    println!("Forgot {}, kept {}, pinned {}, promoted {}", forgotten, kept, pinned, promoted);
+    report_hook_failures(engram);
+}
+
+/// Say when CLAUDE.md couldn't be rewritten after a promote, which still
+/// went through. Returns whether the sync succeeded.
+fn report_sync(synced: &engram::Result<()>) -> bool {
+    if let Err(e) = synced {
+        eprintln!("Promoted, but syncing CLAUDE.md failed: {}", e);
     }
+    synced.is_ok()
+}
+
+/// Warn about promote/forget hooks that failed; the changes themselves stand
+fn report_hook_failures(engram: &Engram) {
+    for failure in engram.take_hook_failures() {
+        eprintln!("Warning: hook failed for {}", failure);
+    }
+}
+
+/// Prompt until the answer is one of f/k/n/p/s/q; end of input quits
+fn ask_review() -> char {
+    loop {
+        eprint!("[f]orget, [k]eep, pi[n], [p]romote, [s]kip, [q]uit? ");
+        let mut answer = String::new();
+        match std::io::stdin().read_line(&mut answer) {
+            Ok(0) | Err(_) => return 'q',
+            Ok(_) => {}
+        }
+        if let Some(c @ ('f' | 'k' | 'n' | 'p' | 's' | 'q')) = answer.trim().to_lowercase().chars().next() {
+            return c;
+        }
+    }
+}
+
+/// Read replacement content from stdin, minus the trailing newline
+fn read_stdin_content() -> Result<String, String> {
+    let mut input = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+    Ok(strip_final_newline(input))
+}
+
+/// A file that is removed when dropped, whichever way the caller exits
+struct TempFile(std::path::PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Write `contents` to a new file only the current user can read. The name
+/// is random and creation fails if it exists, so nothing planted at the
+/// path (like a symlink) is followed.
+fn private_temp_file(contents: &str) -> std::io::Result<TempFile> {
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("engram-edit-{}.md", uuid::Uuid::new_v4().simple()));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path)?;
+    let temp = TempFile(path);
+    file.write_all(contents.as_bytes())?;
+    Ok(temp)
+}
+
+/// Let the user revise `initial` in $VISUAL or $EDITOR (default vi) and
+/// return what they saved
+fn edit_in_editor(initial: &str) -> Result<String, String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let temp = private_temp_file(&format!("{}\n", initial)).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    let path = &temp.0;
+
+    // Through the shell, so EDITOR can carry arguments (e.g. "code --wait")
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("engram")
+        .arg(path)
+        .status();
+    match status {
+        Ok(status) if status.success() => std::fs::read_to_string(path)
+            .map(strip_final_newline)
+            .map_err(|e| format!("Failed to read edited file: {}", e)),
+        Ok(status) => Err(format!("Editor exited with {}; memory left unchanged.", status)),
+        Err(e) => Err(format!("Failed to run editor '{}': {}", editor, e)),
+    }
+}
+
+/// Write `export` as JSON to `path`, flushed and synced before returning so
+/// success means it's on disk, not still sitting in a buffer
+fn write_export(path: &std::path::Path, export: &engram::StoreExport) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, export)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()
+}
+
+/// Editors and `echo` add a final newline that isn't part of the content
+fn strip_final_newline(mut s: String) -> String {
+    if s.ends_with('\n') {
+        s.pop();
+        if s.ends_with('\r') {
+            s.pop();
+        }
+    }
+    s
+}
+
+/// Cut `memories` to `limit`, returning how many were dropped
+fn apply_limit(memories: &mut Vec<engram::Memory>, limit: Option<usize>) -> usize {
+    let total = memories.len();
+    if let Some(limit) = limit {
+        memories.truncate(limit);
+    }
+    total - memories.len()
+}
+
+/// Footer for a listing cut short by --limit
+fn print_hidden(hidden: usize) {
+    if hidden > 0 {
+        println!("... and {} more (use --limit to see)", hidden);
+    }
+}
+
+/// Views built from TAP events undercount when only some taps are logged
+fn warn_tap_sampling(engram: &Engram) {
+    let rate = engram.config().event_policy.tap_sample_rate;
+    if rate < 1.0 {
+        eprintln!("Note: only {:.0}% of taps are logged (ENGRAM_TAP_EVENT_SAMPLE); tap counts here are sampled.", rate * 100.0);
+    }
+}
+
+/// How mutating commands report what they did: the usual sentences, or one
+/// porcelain line per memory so every command parses the same way
+struct Output {
+    porcelain: bool,
+    /// Content length from --truncate or ENGRAM_TRUNCATE, if set
+    truncate: Option<usize>,
+}
+
+impl Output {
+    /// Report a successful mutation; `human` is only built when needed
+    fn done(&self, action: &str, id: &str, human: impl FnOnce() -> String) {
+        if self.porcelain {
+            println!("{}", porcelain(action, id, "ok"));
+        } else {
+            println!("{}", human());
+        }
+    }
+
+    /// Report an ID that matched no memory; callers decide the exit code
+    fn not_found(&self, action: &str, id: &str) {
+        if self.porcelain {
+            println!("{}", porcelain(action, id, "not-found"));
+        } else {
+            eprintln!("Memory not found: {}", id);
+        }
+    }
+
+    /// Report a memory left alone because it's already `state`; callers
+    /// decide the exit code
+    fn unchanged(&self, action: &str, id: &str, state: engram::Lifecycle) {
+        if self.porcelain {
+            println!("{}", porcelain(action, id, state.label()));
+        } else {
+            eprintln!("Not changed, already {}: {}", state.label(), id);
+        }
+    }
+
+    /// Cut content to the configured length, or `default` if none is set
+    fn clip(&self, content: &str, default: usize) -> String {
+        truncate(content, self.truncate.unwrap_or(default))
+    }
+
+    /// Print the memories a bulk action applies to
+    fn matched(&self, action: &str, verb: &str, memories: &[engram::Memory], dry_run: bool) {
+        if self.porcelain {
+            let status = if dry_run { "dry-run" } else { "ok" };
+            for m in memories {
+                println!("{}", porcelain(action, &m.id, status));
+            }
+            return;
+        }
+        println!("{} {} memories", verb, memories.len());
+        for m in memories {
+            println!("[{}] taps:{} | {}", m.id, m.tap_count, self.clip(&m.content, MEMORY_WIDTH));
+        }
+    }
+}
+
+/// Safety limits for `add -` so an accidental pipe can't flood the store
+const MAX_STDIN_LINES: usize = 100;
+/// Batches from stdin larger than this are summarized and need --yes
+const CONFIRM_STDIN_LINES: usize = 10;
+const MAX_STDIN_BYTES: usize = 64 * 1024;
+
+/// Read memories from stdin, one per non-empty line.
+/// Refuses input above the safety limits unless `force` is set.
+fn read_stdin_memories(force: bool) -> Result<Vec<String>, String> {
+    use std::io::Read;
+
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+
+    let lines: Vec<String> = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+
+    if !force && (lines.len() > MAX_STDIN_LINES || input.len() > MAX_STDIN_BYTES) {
+        return Err(format!(
+            "Refusing to add {} memories ({} bytes) from stdin; limit is {} lines / {} bytes.\n\
+             First line: {}\n\
+             Re-run with --force if this is intentional.",
+            lines.len(),
+            input.len(),
+            MAX_STDIN_LINES,
+            MAX_STDIN_BYTES,
+            lines.first().map(|l| truncate(l, MEMORY_WIDTH)).unwrap_or_default()
+        ));
+    }
+
+    Ok(lines)
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    let engram = match Engram::from_env() {
+    let profile = cli
+        .profile
+        .clone()
+        .unwrap_or_else(|| std::env::var("ENGRAM_PROFILE").unwrap_or_else(|_| "global".to_string()));
+    if let Err(e) = parse_profile(&profile) {
+        eprintln!("Invalid ENGRAM_PROFILE: {}", e);
+        std::process::exit(1);
+    }
+
+    // Commands that only read use a read-only connection to avoid
+    // contending with writers
+    let read_only = matches!(
+        cli.command,
+        Commands::List { .. } | Commands::Search { .. } | Commands::Show { .. } | Commands::Log { .. } | Commands::Hot { .. } | Commands::Recent { .. } | Commands::Activity { .. } | Commands::Stats { .. }
+            | Commands::Metrics | Commands::Doctor { fix_events: false, .. } | Commands::Export { .. }
+    );
+
+    // Profiles used to live in ~/.engram/profiles; move them once, and say
+    // so. Read-only commands leave that to the next write unless they open
+    // a named profile, which creates the new profiles directory and would
+    // strand the old one.
+    let opens_profile_dir = profile != "global" && std::env::var_os("ENGRAM_DB_PATH").is_none();
+    if !read_only || opens_profile_dir {
+        match engram::migrate_legacy_profiles() {
+            Ok(Some((from, to))) => eprintln!("Moved profiles from {} to {}", from.display(), to.display()),
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: couldn't migrate profiles: {}", e),
+        }
+    }
+
+    if let Commands::Profiles = cli.command {
+        match engram::list_profiles() {
+            Ok(profiles) if profiles.is_empty() => println!("No profiles found."),
+            Ok(profiles) => {
+                for p in profiles {
+                    let marker = if p.name == profile { "*" } else { " " };
+                    println!("{} {:16} {:>10}  {}", marker, p.name, format_bytes(p.size_bytes), p.db_path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to list profiles: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let config = engram::Config::from_env_for_profile(&profile);
+    let opened = if read_only {
+        Engram::open_readonly(&config)
+    } else {
+        Engram::new(&config)
+    };
+
+    let engram = match opened {
         Ok(e) => e,
         Err(e) => {
             eprintln!("Failed to open database: {}", e);
@@ -99,26 +761,174 @@ fn main() {
         }
     };
 
+    let out = Output {
+        porcelain: cli.porcelain,
+        truncate: cli.truncate.or_else(|| std::env::var("ENGRAM_TRUNCATE").ok().and_then(|n| n.parse().ok())),
+    };
+
     match cli.command {
-        Commands::Add { content } => {
+        Commands::Add { content, kind, fields, force, allow_secrets, dedup_threshold, yes } => {
+            if kind.is_some() || !fields.is_empty() {
+                if content.as_deref() == Some("-") {
+                    eprintln!("Structured memories can't be read from stdin.");
+                    std::process::exit(1);
+                }
+                if !allow_secrets {
+                    let values = content.iter().chain(fields.iter().map(|(_, v)| v));
+                    for value in values {
+                        if let Some(reason) = engram::detect_secret(value) {
+                            eprintln!("Content looks like it contains a secret ({}).", reason);
+                            eprintln!("Re-run with --allow-secrets to store it anyway.");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                let kind = kind.unwrap_or_else(|| "plain".to_string());
+                match engram.add_structured_memory(content.as_deref(), &kind, &fields) {
+                    Ok(id) => out.done("add", &id, || id.clone()),
+                    Err(e) => {
+                        eprintln!("Failed to add memory: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
+            // clap guarantees content is present without --field
+            let content = content.unwrap_or_default();
+            if content == "-" {
+                let lines = match read_stdin_memories(force) {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                if lines.len() > CONFIRM_STDIN_LINES && !yes {
+                    eprintln!("About to add {} memories from stdin:", lines.len());
+                    for line in lines.iter().take(3) {
+                        eprintln!("  {}", truncate(line, MEMORY_WIDTH));
+                    }
+                    eprintln!("  ... and {} more", lines.len() - 3);
+                    eprintln!("Re-run with --yes to add them.");
+                    std::process::exit(1);
+                }
+                let report = match engram.add_memories(&lines, allow_secrets, dedup_threshold) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("Failed to add memories: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+                for id in &report.added {
+                    out.done("add", id, || id.clone());
+                }
+                // By position, so a line flagged as a secret isn't echoed back
+                for (i, reason) in &report.skipped {
+                    eprintln!("Skipped entry {} of {}: {}", i + 1, lines.len(), reason);
+                }
+                if !report.skipped.is_empty() {
+                    eprintln!("Added {}, skipped {}.", report.added.len(), report.skipped.len());
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            if !allow_secrets {
+                if let Some(reason) = engram::detect_secret(&content) {
+                    eprintln!("Content looks like it contains a secret ({}).", reason);
+                    eprintln!("Re-run with --allow-secrets to store it anyway.");
+                    std::process::exit(1);
+                }
+            }
+
+            if let Some(threshold) = dedup_threshold {
+                match engram.find_similar(&content, threshold) {
+                    Ok(None) => {}
+                    Ok(Some((similar, score))) => {
+                        eprintln!("A similar memory already exists ({:.0}% similar):", score * 100.0);
+                        eprintln!("[{}] {}", similar.id, sanitize(&similar.content));
+                        eprintln!("Tap it instead with: engram tap {}", similar.id);
+                        std::process::exit(1);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to check for similar memories: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
             match engram.add_memory(&content) {
-                Ok(id) => println!("{}", id),
+                Ok(id) => out.done("add", &id, || id.clone()),
                 Err(e) => {
                     eprintln!("Failed to add memory: {}", e);
                     std::process::exit(1);
                 }
             }
         }
-        Commands::List { all } => {
+        Commands::List { all, kind, origin, content_kind, untapped, tapped, stale, sort, json_lines, long, width, limit } => {
+            let stale_cutoff = stale.map(|age| chrono::Local::now().fixed_offset() - age);
+            let matches = |m: &engram::Memory| {
+                kind.as_deref().is_none_or(|k| m.kind == k)
+                    && (!untapped || m.tap_count == 0)
+                    && (!tapped || m.tap_count > 0)
+                    && content_kind.as_deref().is_none_or(|k| m.content_kind == k)
+                    && origin.as_deref().is_none_or(|o| engram::origin_matches(m, o))
+                    && stale_cutoff.is_none_or(|cutoff| engram::last_tapped(m) < Some(cutoff))
+            };
+            if json_lines {
+                use std::io::Write;
+                use std::ops::ControlFlow;
+
+                let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+                let result = engram.for_each_memory(all, |m| {
+                    if !matches(&m) {
+                        return ControlFlow::Continue(());
+                    }
+                    // A write error (e.g. closed pipe) just stops the stream
+                    match serde_json::to_writer(&mut out, &m).map_err(std::io::Error::from)
+                        .and_then(|_| writeln!(out))
+                    {
+                        Ok(()) => ControlFlow::Continue(()),
+                        Err(_) => ControlFlow::Break(()),
+                    }
+                });
+                let _ = out.flush();
+                if let Err(e) = result {
+                    eprintln!("Failed to list memories: {}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
+
             match engram.list_memories_filtered(all) {
                 Ok(memories) => {
-                    if memories.is_empty() {
+                    let mut memories: Vec<_> = memories
+                        .into_iter()
+                        .filter(|m| matches(m))
+                        .collect();
+                    match sort {
+                        // Already the store's order
+                        ListSort::Taps => {}
+                        ListSort::Created => memories.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+                        ListSort::LastTapped => memories.sort_by_key(|m| std::cmp::Reverse(engram::last_tapped(m))),
+                    }
+                    let width = wrap_width(width);
+                    let hidden = apply_limit(&mut memories, limit);
+                    if memories.is_empty() && hidden == 0 {
                         println!("No memories found.");
                     } else {
                         for m in memories {
-                            println!("[{}] taps:{} | {}", m.id, m.tap_count, m.content);
+                            let pin = if m.pinned { " [P]" } else { "" };
+                            if long {
+                                println!("[{}]{} taps:{}", m.id, pin, m.tap_count);
+                                println!("    {}", wrap(&sanitize_block(&m.content), width, 4));
+                            } else {
+                                println!("[{}]{} taps:{} | {}", m.id, pin, m.tap_count, out.clip(&m.content, 0));
+                            }
                         }
                     }
+                    print_hidden(hidden);
                 }
                 Err(e) => {
                     eprintln!("Failed to list memories: {}", e);
@@ -126,13 +936,67 @@ fn main() {
                 }
             }
         }
-        Commands::Show { id } => {
+        Commands::Search { pattern, all, semantic, whole_word, limit } => {
+            let color = cli.color.enabled();
+            let results = if semantic {
+                engram.search_semantic(&pattern, all)
+            } else if whole_word {
+                engram.search_whole_word(&pattern, all)
+            } else {
+                engram.search_memories(&pattern, all)
+            };
+            match results {
+                Ok(mut memories) => {
+                    let hidden = apply_limit(&mut memories, limit);
+                    if memories.is_empty() && hidden == 0 {
+                        println!("No memories match \"{}\".", pattern);
+                    } else {
+                        for m in memories {
+                            println!(
+                                "[{}] taps:{} | {}",
+                                m.id,
+                                m.tap_count,
+                                highlight_matches(&sanitize(&m.content), &pattern, color)
+                            );
+                        }
+                    }
+                    print_hidden(hidden);
+                }
+                Err(e) => {
+                    eprintln!("Failed to search memories: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Show { id, raw, width } => {
             match engram.get_memory(&id) {
+                Ok(Some(m)) if raw => print!("{}", m.content),
                 Ok(Some(m)) => {
                     println!("ID:         {}", m.id);
-                    println!("Content:    {}", m.content);
+                    if m.content_kind == "code" {
+                        println!("Content:\n{}", frame_code(&m.content));
+                    } else {
+                        println!("Content:    {}", wrap(&sanitize_block(&m.content), wrap_width(width), 12));
+                    }
+                    if m.kind != "plain" {
+                        println!("Kind:       {}", m.kind);
+                    }
+                    println!("Looks like: {}", m.content_kind);
+                    for (key, value) in engram::parse_fields(&m) {
+                        println!("  {}: {}", key, sanitize_block(&value));
+                    }
                     println!("Taps:       {}", m.tap_count);
+                    if let Some(score) = engram.effective_score(&m) {
+                        println!("Score:      {:.2} (decayed)", score);
+                    }
+                    println!("Version:    {}", m.version);
+                    if m.pinned {
+                        println!("Pinned:     yes");
+                    }
                     println!("Created:    {}", m.created_at);
+                    if let Some(ref origin) = m.origin {
+                        println!("Origin:     {}", origin);
+                    }
                     if let Some(ref tapped) = m.last_tapped_at {
                         println!("Last tap:   {}", tapped);
                     }
@@ -147,11 +1011,46 @@ fn main() {
                 }
             }
         }
-        Commands::Edit { id, content } => {
-            match engram.edit_memory(&id, &content) {
-                Ok(true) => println!("Updated: {}", id),
+        Commands::Edit { id, content, edit, mut expect_version } => {
+            let content = match content.as_deref() {
+                Some("-") | None => {
+                    let current = match engram.get_memory(&id) {
+                        Ok(Some(m)) => m,
+                        Ok(None) => {
+                            out.not_found("edit", &id);
+                            std::process::exit(1);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to get memory: {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    let revised = if edit { edit_in_editor(&current.content) } else { read_stdin_content() };
+                    let revised = match revised {
+                        Ok(revised) => revised,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    if revised.trim().is_empty() {
+                        eprintln!("Empty content; memory left unchanged.");
+                        std::process::exit(1);
+                    }
+                    if revised == current.content {
+                        println!("No changes.");
+                        return;
+                    }
+                    // Don't overwrite a change made while the editor was open
+                    expect_version.get_or_insert(current.version);
+                    revised
+                }
+                Some(content) => content.to_string(),
+            };
+            match engram.edit_memory(&id, &content, expect_version) {
+                Ok(true) => out.done("edit", &id, || format!("Updated: {}", id)),
                 Ok(false) => {
-                    eprintln!("Memory not found: {}", id);
+                    out.not_found("edit", &id);
                     std::process::exit(1);
                 }
                 Err(e) => {
@@ -160,11 +1059,73 @@ fn main() {
                 }
             }
         }
-        Commands::Forget { id } => {
+        Commands::Forget { match_str: Some(pattern), dry_run, yes, include_pinned, .. } => {
+            let matched = match engram.matching(&pattern, include_pinned) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    eprintln!("Failed to match memories: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if dry_run || matched.is_empty() {
+                out.matched("forget", "Would forget", &matched, true);
+                return;
+            }
+            if !yes && !confirm(&format!("Forget {} memories matching \"{}\"?", matched.len(), pattern)) {
+                std::process::exit(1);
+            }
+            let ids: Vec<String> = matched.iter().map(|m| m.id.clone()).collect();
+            match engram.forget_ids(&ids) {
+                Ok(forgotten) => out.matched("forget", "Forgot", &forgotten, false),
+                Err(e) => {
+                    eprintln!("Failed to forget memories: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Forget { id: None, older_than, untapped, dry_run, interactive, yes, .. } => {
+            let filter = engram::ForgetFilter {
+                created_before: older_than.map(|age| (chrono::Local::now() - age).to_rfc3339()),
+                max_taps: untapped.then_some(0),
+            };
+            let matched = match engram.memories_where(&filter) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    eprintln!("Failed to match memories: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if interactive && std::io::stdin().is_terminal() {
+                review_candidates(&engram, &matched);
+                return;
+            }
+            if interactive {
+                eprintln!("No terminal for --interactive; forgetting in bulk instead.");
+            }
+            if dry_run || matched.is_empty() {
+                out.matched("forget", "Would forget", &matched, true);
+                return;
+            }
+            if !yes && !confirm(&format!("Forget {} memories?", matched.len())) {
+                std::process::exit(1);
+            }
+            let ids: Vec<String> = matched.iter().map(|m| m.id.clone()).collect();
+            match engram.forget_ids(&ids) {
+                Ok(forgotten) => out.matched("forget", "Forgot", &forgotten, false),
+                Err(e) => {
+                    eprintln!("Failed to forget memories: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Forget { id: Some(id), .. } => {
             match engram.forget_memory(&id) {
-                Ok(true) => println!("Forgotten: {}", id),
+                Ok(true) => out.done("forget", &id, || format!("Forgotten: {}", id)),
                 Ok(false) => {
-                    eprintln!("Memory not found: {}", id);
+                    match engram.lifecycle(&id) {
+                        Ok(Some(state)) => out.unchanged("forget", &id, state),
+                        _ => out.not_found("forget", &id),
+                    }
                     std::process::exit(1);
                 }
                 Err(e) => {
@@ -173,14 +1134,58 @@ fn main() {
                 }
             }
         }
-        Commands::Promote { id } => {
+        Commands::Promote { match_str: Some(pattern), dry_run, yes, include_pinned, .. } => {
+            let matched = match engram.matching(&pattern, include_pinned) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    eprintln!("Failed to match memories: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if dry_run || matched.is_empty() {
+                out.matched("promote", "Would promote", &matched, true);
+                return;
+            }
+            if !yes && !confirm(&format!("Promote {} memories matching \"{}\"?", matched.len(), pattern)) {
+                std::process::exit(1);
+            }
+            let ids: Vec<String> = matched.iter().map(|m| m.id.clone()).collect();
+            match engram.promote_ids(&ids) {
+                Ok(engram::PromoteResult { promoted, synced }) => {
+                    if out.porcelain {
+                        out.matched("promote", "Promoted", &promoted, false);
+                    } else {
+                        // Output markdown format for CLAUDE.md
+                        for m in promoted {
+                            println!("- {}", m.content);
+                        }
+                    }
+                    if !report_sync(&synced) {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to promote memories: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Promote { id, .. } => {
+            // clap requires an ID when --match isn't given
+            let id = id.unwrap_or_default();
             match engram.promote_memory(&id) {
-                Ok(Some(content)) => {
+                Ok(engram::PromoteResult { promoted: Some(content), synced }) => {
                     // Output markdown format for CLAUDE.md
-                    println!("- {}", content);
+                    out.done("promote", &id, || format!("- {}", content));
+                    if !report_sync(&synced) {
+                        std::process::exit(1);
+                    }
                 }
-                Ok(None) => {
-                    eprintln!("Memory not found: {}", id);
+                Ok(engram::PromoteResult { promoted: None, .. }) => {
+                    match engram.lifecycle(&id) {
+                        Ok(Some(state)) => out.unchanged("promote", &id, state),
+                        _ => out.not_found("promote", &id),
+                    }
                     std::process::exit(1);
                 }
                 Err(e) => {
@@ -189,14 +1194,48 @@ fn main() {
                 }
             }
         }
-        Commands::Tap { ids, match_str } => {
+        Commands::Pin { id } => {
+            match engram.pin_memory(&id) {
+                Ok(true) => out.done("pin", &id, || format!("Pinned: {}", id)),
+                Ok(false) => {
+                    out.not_found("pin", &id);
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Failed to pin memory: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Unpin { id } => {
+            match engram.unpin_memory(&id) {
+                Ok(true) => out.done("unpin", &id, || format!("Unpinned: {}", id)),
+                Ok(false) => {
+                    out.not_found("unpin", &id);
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Failed to unpin memory: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Tap { ids, match_str, whole_word, force, count, revive } => {
             let mut tapped = Vec::new();
-            let mut not_found = Vec::new();
+            let mut matched = Vec::new();
 
             // Tap by match pattern first
-            if let Some(pattern) = match_str {
-                match engram.tap_memories_by_match(&pattern) {
-                    Ok(matched_ids) => tapped.extend(matched_ids),
+            let blank_match = match_str.as_deref().is_some_and(|p| p.trim().is_empty());
+            if blank_match && !force {
+                eprintln!("An empty --match pattern would tap every memory; pass --force to do that.");
+                std::process::exit(1);
+            }
+            if let Some(pattern) = match_str.as_deref() {
+                match engram.tap_memories_by_match(pattern, count, whole_word, force) {
+                    Ok(matched_ids) => {
+                        tapped.extend(matched_ids.iter().cloned());
+                        matched = matched_ids;
+                    }
                     Err(e) => {
                         eprintln!("Failed to tap by match: {}", e);
                         std::process::exit(1);
@@ -204,41 +1243,104 @@ fn main() {
                 }
             }
 
-            // Tap by explicit IDs
+            // Tap by explicit IDs, expanding "-" to IDs read from stdin
+            let mut explicit = Vec::new();
             for id in ids {
-                match engram.tap_memory(&id) {
-                    Ok(true) => tapped.push(id),
-                    Ok(false) => not_found.push(id),
-                    Err(e) => {
-                        eprintln!("Failed to tap {}: {}", id, e);
+                if id == "-" {
+                    let mut input = String::new();
+                    if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut input) {
+                        eprintln!("Failed to read stdin: {}", e);
                         std::process::exit(1);
                     }
+                    explicit.extend(input.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from));
+                } else {
+                    explicit.push(id);
                 }
             }
+            let report = match engram.tap_memories(&explicit, count, revive) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Failed to tap memories: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            tapped.extend(report.tapped);
+            let missed = !report.not_found.is_empty() || !report.forgotten.is_empty();
 
-            if tapped.is_empty() && not_found.is_empty() {
+            if out.porcelain {
+                for id in &report.revived {
+                    out.done("revive", id, String::new);
+                }
+                for id in &tapped {
+                    out.done("tap", id, String::new);
+                }
+                for id in &report.not_found {
+                    out.not_found("tap", id);
+                }
+                for id in &report.forgotten {
+                    println!("{}", porcelain("tap", id, "forgotten"));
+                }
+            } else if tapped.is_empty() && !missed {
                 println!("No memories to tap.");
             } else {
+                if !report.revived.is_empty() {
+                    println!("Revived {} memory(ies): {}", report.revived.len(), report.revived.join(", "));
+                }
                 if !tapped.is_empty() {
                     println!("Tapped {} memory(ies): {}", tapped.len(), tapped.join(", "));
                 }
-                if !not_found.is_empty() {
-                    eprintln!("Not found: {}", not_found.join(", "));
-                    std::process::exit(1);
+                // Show where the pattern hit, like `search` does
+                if let Some(pattern) = match_str.as_deref() {
+                    let color = cli.color.enabled();
+                    for id in &matched {
+                        if let Ok(Some(m)) = engram.get_memory(id) {
+                            println!(
+                                "[{}] taps:{} | {}",
+                                m.id,
+                                m.tap_count,
+                                highlight_matches(&sanitize(&m.content), pattern, color)
+                            );
+                        }
+                    }
+                }
+                if !report.not_found.is_empty() {
+                    eprintln!("Not found: {}", report.not_found.join(", "));
+                }
+                for id in &report.forgotten {
+                    eprintln!("Not tapped, forgotten: {} (use --revive to bring it back)", id);
                 }
             }
+            if missed {
+                std::process::exit(1);
+            }
         }
-        Commands::Log { limit, action, memory, all } => {
-            match engram.get_enriched_events(limit, action.as_deref(), memory.as_deref(), all) {
+        Commands::Log { limit, action, memory, grep, all, no_reviews, json_lines } => {
+            let exclude = no_reviews.then_some("REVIEW");
+            match engram.get_enriched_events(limit, action.as_deref(), exclude, memory.as_deref(), grep.as_deref(), all) {
+                Ok(events) if json_lines => {
+                    use std::io::Write;
+
+                    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+                    for e in &events {
+                        // A write error (e.g. closed pipe) just stops the stream
+                        let written = serde_json::to_writer(&mut out, e).map_err(std::io::Error::from)
+                            .and_then(|_| writeln!(out));
+                        if written.is_err() {
+                            break;
+                        }
+                    }
+                    let _ = out.flush();
+                }
                 Ok(events) => {
+                    let color = cli.color.enabled();
                     if events.is_empty() {
                         println!("No events found.");
                     } else {
                         for e in events {
                             let mem_id = e.memory_id.as_deref().unwrap_or("-");
                             let short_id = if mem_id.len() > 8 { &mem_id[..8] } else { mem_id };
-                            print!("{} {:8} {}", e.timestamp, e.action, short_id);
-                            print!(" {}", truncate(&e.content, 50));
+                            print!("{} {} {}", e.timestamp, action_label(&e.action, 8, color), short_id);
+                            print!(" {}", out.clip(&e.content, EVENT_WIDTH));
                             println!();
                         }
                     }
@@ -249,19 +1351,300 @@ fn main() {
                 }
             }
         }
-        Commands::Init => {
+        Commands::Hot { hours, compare } => {
+            warn_tap_sampling(&engram);
+            let hours = hours.unwrap_or(engram.config().hot_hours);
+            if let Some(long_hours) = compare {
+                match engram.compare_hot_memories(hours, long_hours) {
+                    Ok(rows) => {
+                        if rows.is_empty() {
+                            println!("No memories tapped in the last {}h.", long_hours);
+                        } else {
+                            for r in rows {
+                                println!(
+                                    "[{}] {}h:{} {}h:{} {} | {}",
+                                    r.memory.id, hours, r.short_taps, long_hours, r.long_taps,
+                                    r.trend.arrow(), sanitize(&r.memory.content)
+                                );
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to get hot memories: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                match engram.get_hot_memories(hours) {
+                    Ok(hot) => {
+                        if hot.is_empty() {
+                            println!("No memories tapped in the last {}h.", hours);
+                        } else {
+                            for h in hot {
+                                println!("[{}] taps:{} | {}", h.memory.id, h.recent_taps, sanitize(&h.memory.content));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to get hot memories: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        Commands::Recent { hours, limit } => {
+            warn_tap_sampling(&engram);
+            let hours = hours.unwrap_or(engram.config().hot_hours);
+            match engram.get_recent_memories(hours) {
+                Ok(recent) => {
+                    if recent.is_empty() {
+                        println!("No memories added or tapped in the last {}h.", hours);
+                    }
+                    for r in recent.iter().take(limit) {
+                        let taps = if r.recent_taps > 0 { format!(" taps:{}", r.recent_taps) } else { String::new() };
+                        println!("[{}] {:6}{} | {}", r.memory.id, r.reason.as_str(), taps, sanitize(&r.memory.content));
+                    }
+                    print_hidden(recent.len().saturating_sub(limit));
+                }
+                Err(e) => {
+                    eprintln!("Failed to get recent memories: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Note { action, data, id } => {
+            match engram.log_custom_event(&action, id.as_deref(), &data) {
+                Ok(()) => println!("Recorded: {}", action),
+                Err(e) => {
+                    eprintln!("Failed to record event: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Activity { days, sparkline: as_sparkline, actions } => {
+            warn_tap_sampling(&engram);
+            match engram.get_activity_by_day(days, &actions) {
+                Ok(activity) => {
+                    if as_sparkline {
+                        for (i, label) in actions.iter().enumerate() {
+                            let values: Vec<u64> = activity.iter().map(|d| d.counts[i] as u64).collect();
+                            let total: u64 = values.iter().sum();
+                            println!("{:8} {} {}", label, sparkline(&values), total);
+                        }
+                    } else {
+                        let widths: Vec<usize> = actions.iter().map(|a| a.len().max(5)).collect();
+                        let mut header = format!("{:10}", "Day");
+                        for (action, width) in actions.iter().zip(&widths) {
+                            header.push_str(&format!(" {:>width$}", action, width = width));
+                        }
+                        println!("{}", header);
+                        for d in activity {
+                            let mut row = format!("{:10}", d.day);
+                            for (count, width) in d.counts.iter().zip(&widths) {
+                                row.push_str(&format!(" {:>width$}", count, width = width));
+                            }
+                            println!("{}", row);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to get activity: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Stats { trend: Some(days) } => {
+            match engram.get_stats_trend(days) {
+                Ok(trend) => {
+                    let values: Vec<u64> = trend.iter().map(|d| d.active as u64).collect();
+                    let first = values.first().copied().unwrap_or(0);
+                    let last = values.last().copied().unwrap_or(0);
+                    println!("Active     {} {} -> {}", sparkline(&values), first, last);
+                    for d in trend {
+                        println!("{}  {}", d.day, d.active);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to get stats trend: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Stats { trend: None } => {
+            match engram.get_stats() {
+                Ok(stats) => {
+                    let health = engram::health_score(&stats);
+                    match health.detractor {
+                        Some(reason) => println!("Health:     {}/100 ({})", health.score, reason),
+                        None => println!("Health:     {}/100", health.score),
+                    }
+                    println!("Memories:   {}", stats.total_memories);
+                    println!("  active:   {}", stats.active);
+                    println!("  promoted: {}", stats.promoted);
+                    println!("  forgotten: {}", stats.forgotten);
+                    println!("Taps:       {}", stats.total_taps);
+                    println!("Events:     {}", stats.total_events);
+                    println!("DB size:    {}", format_bytes(stats.db_size_bytes));
+                    if let Some(wal) = stats.wal_size_bytes {
+                        println!("WAL size:   {}", format_bytes(wal));
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to get stats: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Metrics => {
+            match engram.metrics() {
+                Ok(text) => print!("{}", text),
+                Err(e) => {
+                    eprintln!("Failed to get metrics: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Doctor { fix_events: true, .. } => {
+            match engram.repair_events(false) {
+                Ok(count) => println!("Repaired {} events", count),
+                Err(e) => {
+                    eprintln!("Failed to repair events: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Doctor { json, .. } => {
+            let health = match engram.check_health() {
+                Ok(health) => health,
+                Err(e) => {
+                    eprintln!("Failed to check store: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if json {
+                match serde_json::to_string_pretty(&health) {
+                    Ok(s) => println!("{}", s),
+                    Err(e) => {
+                        eprintln!("Failed to serialize report: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            let yes_no = |b: bool| if b { "yes" } else { "no" };
+            println!("Database:   {}", health.db_path);
+            println!("Schema:     {}", if health.schema_current { "current" } else { "needs migration (open once writable)" });
+            println!("SQLite:     {} (FTS5: {})", health.sqlite_version, yes_no(health.fts5_available));
+            println!("Journal:    {}", health.journal_mode);
+            println!("DB size:    {}", format_bytes(health.stats.db_size_bytes));
+            if let Some(wal) = health.stats.wal_size_bytes {
+                println!("WAL size:   {}", format_bytes(wal));
+            }
+            println!("Memories:   {} ({} active, {} promoted, {} forgotten)",
+                health.stats.total_memories, health.stats.active, health.stats.promoted, health.stats.forgotten);
+            println!("Events:     {}", health.stats.total_events);
+            println!("  orphaned: {}", health.orphaned_events);
+            if health.malformed_events > 0 {
+                println!("  malformed: {} (run `engram doctor --fix-events`)", health.malformed_events);
+            } else {
+                println!("  malformed: 0");
+            }
+            println!("  future:   {}", health.future_events);
+            if health.future_memories > 0 || health.future_events > 0 {
+                println!("Clock skew: {} memories and {} events dated in the future; they're left out of hot and activity",
+                    health.future_memories, health.future_events);
+            }
+        }
+        Commands::Export { output } => {
+            let export = match engram.export_store() {
+                Ok(export) => export,
+                Err(e) => {
+                    eprintln!("Failed to export store: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let result = match &output {
+                Some(path) => write_export(path, &export),
+                None => serde_json::to_writer_pretty(std::io::stdout().lock(), &export).map_err(Into::into),
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to write export: {}", e);
+                std::process::exit(1);
+            }
+            match output {
+                Some(path) => eprintln!(
+                    "Exported {} memories and {} events to {}",
+                    export.memories.len(),
+                    export.events.len(),
+                    path.display()
+                ),
+                None => println!(),
+            }
+        }
+        Commands::Import { path } => {
+            let parsed: Result<engram::StoreExport, std::io::Error> = if path.as_os_str() == "-" {
+                serde_json::from_reader(std::io::stdin().lock()).map_err(Into::into)
+            } else {
+                std::fs::File::open(&path)
+                    .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).map_err(Into::into))
+            };
+            let export = match parsed {
+                Ok(export) => export,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            match engram.import_store(&export) {
+                Ok((imported, skipped)) => {
+                    println!("Imported {} memories ({} already present)", imported, skipped);
+                }
+                Err(e) => {
+                    eprintln!("Failed to import: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Sync { path } => {
+            let path = path
+                .or_else(|| engram.config().claude_md_path.clone())
+                .unwrap_or_else(|| std::path::PathBuf::from("CLAUDE.md"));
+            match engram.sync_claude_md(&path) {
+                Ok(count) => println!("Synced {} promoted memories to {}", count, path.display()),
+                Err(e) => {
+                    eprintln!("Failed to sync {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Profiles => unreachable!("handled before opening a store"),
+        Commands::Init { budget } => {
             // DB is already opened/created above, just confirm
-            println!("Initialized engram in .engram/");
+            println!("Initialized engram in {}", engram.config().db_path.display());
+            if let Some(budget) = budget {
+                match engram.select_within_budget(budget) {
+                    Ok(memories) => {
+                        for m in memories {
+                            println!("- {}", sanitize(&m.content));
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to select memories: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
         }
         Commands::Prime => {
             print!("{}", include_str!("AGENT_INSTRUCTIONS.md"));
         }
         Commands::Ui => {
-            if let Err(e) = tui::run() {
+            if let Err(e) = tui::run(&engram) {
                 eprintln!("TUI error: {}", e);
                 std::process::exit(1);
             }
         }
     }
+    report_hook_failures(&engram);
 }
 
@@ -0,0 +1,61 @@
+//! Near-duplicate detection for new memories
+//!
+//! Paraphrased memories rarely share exact wording, so content is compared
+//! as sets of character trigrams. Cheap enough to scan every active memory
+//! on add, and insensitive to case, punctuation and spacing.
+
+use std::collections::HashSet;
+
+/// A text's trigrams, computed once to compare against many others
+pub type Trigrams = HashSet<[char; 3]>;
+
+/// Jaccard similarity of the two texts' character trigrams, from 0.0
+/// (nothing in common) to 1.0 (same trigrams)
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    jaccard(&trigrams(a), &trigrams(b))
+}
+
+/// Jaccard similarity of two precomputed trigram sets
+pub fn jaccard(a: &Trigrams, b: &Trigrams) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Trigrams of the lowercased words, each word padded with spaces so
+/// short words still contribute
+pub fn trigrams(text: &str) -> Trigrams {
+    let mut set = HashSet::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let chars: Vec<char> = std::iter::once(' ')
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(std::iter::once(' '))
+            .collect();
+        for w in chars.windows(3) {
+            set.insert([w[0], w[1], w[2]]);
+        }
+    }
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_and_reworded() {
+        assert_eq!(trigram_similarity("Run tests with nextest", "run TESTS with nextest!"), 1.0);
+        let close = trigram_similarity("Run the tests with cargo nextest", "Tests run with cargo nextest");
+        assert!(close > 0.6, "{}", close);
+    }
+
+    #[test]
+    fn test_unrelated_content() {
+        let far = trigram_similarity("Auth tokens expire after 15 minutes", "Deploys go through CI");
+        assert!(far < 0.2, "{}", far);
+        assert_eq!(trigram_similarity("", ""), 1.0);
+        assert_eq!(trigram_similarity("words", ""), 0.0);
+    }
+}
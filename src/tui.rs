@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
 use std::time::Duration;
 
 use crossterm::{
@@ -12,7 +14,22 @@ use ratatui::{
     widgets::{Bar, BarChart, BarGroup, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
 
-use crate::engram::{Engram, EnrichedEvent};
+use crate::display::{
+    action_color, format_timestamp, frame_code, sanitize_block, truncate, EVENT_PANEL_WIDTH, MEMORY_WIDTH, PENDING_WIDTH,
+};
+use engram::{Engram, EnrichedEvent, Memory};
+
+/// How often the background fetcher re-reads the store
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for a key before checking for a new snapshot
+const INPUT_POLL: Duration = Duration::from_millis(50);
+
+/// Events loaded at first, and added each time scrolling nears the end
+const EVENTS_PAGE: u32 = 100;
+
+/// How close to the last loaded event the cursor gets before loading more
+const EVENTS_LOOKAHEAD: usize = 5;
 
 #[derive(PartialEq, Clone, Copy)]
 enum Panel {
@@ -45,6 +62,162 @@ impl ChartMode {
     }
 }
 
+/// Which event actions the events panel shows, mirroring `log --action`
+#[derive(PartialEq, Clone, Copy)]
+enum EventFilter {
+    All,
+    Add,
+    Tap,
+    Review,
+    Edit,
+    Promote,
+    Forget,
+}
+
+impl EventFilter {
+    fn next(self) -> Self {
+        match self {
+            EventFilter::All => EventFilter::Add,
+            EventFilter::Add => EventFilter::Tap,
+            EventFilter::Tap => EventFilter::Review,
+            EventFilter::Review => EventFilter::Edit,
+            EventFilter::Edit => EventFilter::Promote,
+            EventFilter::Promote => EventFilter::Forget,
+            EventFilter::Forget => EventFilter::All,
+        }
+    }
+
+    /// The action to pass to `get_enriched_events`, None for all
+    fn action(self) -> Option<&'static str> {
+        match self {
+            EventFilter::All => None,
+            EventFilter::Add => Some("ADD"),
+            EventFilter::Tap => Some("TAP"),
+            EventFilter::Review => Some("REVIEW"),
+            EventFilter::Edit => Some("EDIT"),
+            EventFilter::Promote => Some("PROMOTE"),
+            EventFilter::Forget => Some("FORGET"),
+        }
+    }
+}
+
+/// A memory action staged from the TUI
+#[derive(PartialEq, Clone, Copy)]
+enum ActionKind {
+    Promote,
+    Forget,
+    Tap,
+}
+
+impl ActionKind {
+    fn label(self) -> &'static str {
+        match self {
+            ActionKind::Promote => "PROMOTE",
+            ActionKind::Forget => "FORGET",
+            ActionKind::Tap => "TAP",
+        }
+    }
+}
+
+/// An action waiting for confirmation, keyed by memory ID so a refresh
+/// that reorders the list can't retarget it
+struct PendingAction {
+    kind: ActionKind,
+    memory_id: String,
+    preview: String,
+}
+
+/// What the fetcher should load; resent whenever the view changes
+#[derive(Clone, Copy)]
+struct FetchRequest {
+    action: Option<&'static str>,
+    include_promoted_taps: bool,
+    events_limit: u32,
+}
+
+/// Everything the TUI renders, loaded off the render loop
+#[derive(Default)]
+struct Snapshot {
+    memories: Vec<Memory>,
+    events: Vec<EnrichedEvent>,
+    /// Rows the events query returned before REVIEW runs were collapsed;
+    /// promoted taps are filtered in SQL, so a full page means there may
+    /// be older ones
+    events_loaded: usize,
+    /// Hourly (adds, taps) for the chart, which ignores the events filter
+    activity: Vec<(String, u64, u64)>,
+}
+
+impl Snapshot {
+    fn fetch(engram: &Engram, request: FetchRequest) -> Self {
+        let memories = engram.list_memories_filtered(false).unwrap_or_default();
+        let events = engram
+            .get_enriched_events(request.events_limit, request.action, None, None, None, request.include_promoted_taps)
+            .unwrap_or_default();
+        let events_loaded = events.len();
+        let activity = if request.action.is_none() {
+            compute_hourly_activity(&events)
+        } else {
+            let all_events = engram
+                .get_enriched_events(EVENTS_PAGE, None, None, None, None, request.include_promoted_taps)
+                .unwrap_or_default();
+            compute_hourly_activity(&all_events)
+        };
+        let events = collapse_reviews(events);
+        Self { memories, events, events_loaded, activity }
+    }
+}
+
+/// Merge each run of REVIEW events logged in the same second (one per
+/// memory on every init, in older stores) into a single `xN` row listing
+/// the memories, so they don't drown out the rest of the timeline
+fn collapse_reviews(events: Vec<EnrichedEvent>) -> Vec<EnrichedEvent> {
+    let second = |e: &EnrichedEvent| e.timestamp.get(..19).map(str::to_string);
+    let mut out: Vec<EnrichedEvent> = Vec::with_capacity(events.len());
+    let mut run: Vec<String> = Vec::new();
+    for e in events {
+        let extends_run = e.action == "REVIEW"
+            && out.last().is_some_and(|last| last.action == "REVIEW" && second(last) == second(&e));
+        if !extends_run {
+            run.clear();
+            if e.action == "REVIEW" {
+                run.extend(e.memory_id.clone());
+            }
+            out.push(e);
+            continue;
+        }
+        run.extend(e.memory_id);
+        if let Some(last) = out.last_mut() {
+            last.memory_id = None;
+            last.content = format!("x{}: {}", run.len(), run.join(", "));
+        }
+    }
+    out
+}
+
+/// Load snapshots on a background thread so slow queries never delay
+/// input: once per request, and again every REFRESH_INTERVAL with the
+/// latest request. The thread exits once the TUI drops either channel.
+fn spawn_fetcher(engram: Engram, first: FetchRequest) -> (Sender<FetchRequest>, Receiver<Snapshot>) {
+    let (request_tx, request_rx) = mpsc::channel();
+    let (snapshot_tx, snapshot_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut request = first;
+        loop {
+            if snapshot_tx.send(Snapshot::fetch(&engram, request)).is_err() {
+                return;
+            }
+            match request_rx.recv_timeout(REFRESH_INTERVAL) {
+                // Skip straight to the newest request if several queued up
+                Ok(newer) => request = request_rx.try_iter().last().unwrap_or(newer),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    });
+    (request_tx, snapshot_rx)
+}
+
 /// Holds the full content for expansion
 struct ExpandedContent {
     title: String,
@@ -57,8 +230,21 @@ struct AppState {
     events_state: ListState,
     memories_count: usize,
     events_count: usize,
+    /// Events fetched before collapsing; fewer than the limit means no more
+    events_loaded: usize,
+    /// Selected items tracked by identity so refreshes don't shift the cursor
+    selected_memory_id: Option<String>,
+    selected_event_id: Option<i64>,
     chart_mode: ChartMode,
+    event_filter: EventFilter,
+    /// Show TAP events for promoted memories, to audit whether they're still used
+    include_promoted_taps: bool,
+    /// How many events to fetch; grows as the cursor nears the end
+    events_limit: u32,
     expanded: Option<ExpandedContent>,
+    pending: Vec<PendingAction>,
+    /// What went wrong in the last commit, shown until the next key press
+    status: Option<String>,
 }
 
 impl AppState {
@@ -73,8 +259,16 @@ impl AppState {
             events_state,
             memories_count: 0,
             events_count: 0,
+            events_loaded: 0,
+            selected_memory_id: None,
+            selected_event_id: None,
             chart_mode: ChartMode::Both,
+            event_filter: EventFilter::All,
+            include_promoted_taps: false,
+            events_limit: EVENTS_PAGE,
             expanded: None,
+            pending: Vec::new(),
+            status: None,
         }
     }
 
@@ -92,6 +286,8 @@ impl AppState {
     }
 
     fn move_down(&mut self) {
+        // Don't wrap while older events may still be loading in
+        let more_events = self.focused == Panel::Events && !self.events_exhausted();
         let (state, count) = match self.focused {
             Panel::Memories => (&mut self.memories_state, self.memories_count),
             Panel::Events => (&mut self.events_state, self.events_count),
@@ -100,10 +296,128 @@ impl AppState {
             return;
         }
         let i = state.selected().unwrap_or(0);
-        let new_i = if i >= count - 1 { 0 } else { i + 1 };
+        let new_i = if i < count - 1 {
+            i + 1
+        } else if more_events {
+            i
+        } else {
+            0
+        };
         state.select(Some(new_i));
     }
 
+    /// A short page means the store has no older events to load
+    fn events_exhausted(&self) -> bool {
+        self.events_loaded < self.events_limit as usize
+    }
+
+    /// Grow the events window when the cursor nears the last loaded event;
+    /// returns whether a bigger fetch is needed
+    fn load_more_events(&mut self) -> bool {
+        let selected = self.events_state.selected().unwrap_or(0);
+        if self.focused != Panel::Events
+            || self.events_exhausted()
+            || selected + EVENTS_LOOKAHEAD < self.events_count
+        {
+            return false;
+        }
+        self.events_limit += EVENTS_PAGE;
+        true
+    }
+
+    /// Re-resolve selected indices after a refresh so the cursor stays on the
+    /// same memory/event; clamps to the end if the item disappeared
+    fn resolve_selection(&mut self, memories: &[Memory], events: &[EnrichedEvent]) {
+        let memory_idx = self
+            .selected_memory_id
+            .as_ref()
+            .and_then(|id| memories.iter().position(|m| &m.id == id));
+        self.memories_state.select(Some(resolve_index(
+            memory_idx,
+            self.memories_state.selected(),
+            memories.len(),
+        )));
+
+        let event_idx = self
+            .selected_event_id
+            .and_then(|id| events.iter().position(|e| e.id == id));
+        self.events_state.select(Some(resolve_index(
+            event_idx,
+            self.events_state.selected(),
+            events.len(),
+        )));
+
+        self.remember_selection(memories, events);
+    }
+
+    /// Record the identity of the currently selected items
+    fn remember_selection(&mut self, memories: &[Memory], events: &[EnrichedEvent]) {
+        self.selected_memory_id = self
+            .memories_state
+            .selected()
+            .and_then(|i| memories.get(i))
+            .map(|m| m.id.clone());
+        self.selected_event_id = self
+            .events_state
+            .selected()
+            .and_then(|i| events.get(i))
+            .map(|e| e.id);
+    }
+
+    /// Stage an action against a memory; nothing is written until commit
+    fn stage(&mut self, kind: ActionKind, memory: &Memory) {
+        self.pending.push(PendingAction {
+            kind,
+            memory_id: memory.id.clone(),
+            preview: truncate(&memory.content, PENDING_WIDTH),
+        });
+    }
+
+    /// Drop the most recently staged action
+    fn undo(&mut self) {
+        self.pending.pop();
+    }
+
+    /// Apply all staged actions in the order they were staged, keeping
+    /// any failures (hooks included) for the status line
+    fn commit(&mut self, engram: &Engram) {
+        let mut failures = Vec::new();
+        for action in self.pending.drain(..) {
+            let result = match action.kind {
+                ActionKind::Promote => match engram.promote_memory(&action.memory_id) {
+                    Ok(promoted) => promoted
+                        .synced
+                        .map_err(|e| format!("promoted, but syncing CLAUDE.md failed: {}", e)),
+                    Err(e) => Err(e.to_string()),
+                },
+                ActionKind::Forget => engram.forget_memory(&action.memory_id).map(|_| ()).map_err(|e| e.to_string()),
+                ActionKind::Tap => engram.tap_memory(&action.memory_id).map(|_| ()).map_err(|e| e.to_string()),
+            };
+            if let Err(e) = result {
+                let short_id = &action.memory_id[..8.min(action.memory_id.len())];
+                failures.push(format!("{} [{}] {}", action.kind.label(), short_id, e));
+            }
+        }
+        failures.extend(engram.take_hook_failures().into_iter().map(|f| format!("hook failed for {}", f)));
+        self.status = (!failures.is_empty()).then(|| failures.join("; "));
+    }
+
+    /// Take the counts and selection from a freshly fetched snapshot
+    fn apply(&mut self, snapshot: &Snapshot) {
+        self.memories_count = snapshot.memories.len();
+        self.events_count = snapshot.events.len();
+        self.events_loaded = snapshot.events_loaded;
+        self.resolve_selection(&snapshot.memories, &snapshot.events);
+    }
+
+    fn fetch_request(&self) -> FetchRequest {
+        FetchRequest {
+            action: self.event_filter.action(),
+            include_promoted_taps: self.include_promoted_taps,
+            events_limit: self.events_limit,
+        }
+    }
+
     fn toggle_panel(&mut self) {
         self.focused = match self.focused {
             Panel::Memories => Panel::Events,
@@ -112,14 +426,14 @@ impl AppState {
     }
 }
 
-pub fn run() -> io::Result<()> {
+pub fn run(engram: &Engram) -> io::Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     io::stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
     // Main loop
-    let result = run_loop(&mut terminal);
+    let result = run_loop(&mut terminal, engram);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -128,29 +442,42 @@ pub fn run() -> io::Result<()> {
     result
 }
 
-fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, engram: &Engram) -> io::Result<()> {
     let mut state = AppState::new();
 
-    let engram = match Engram::from_env() {
-        Ok(e) => e,
-        Err(_) => return Ok(()), // Exit gracefully if DB can't be opened
-    };
+    let (requests, snapshots) = spawn_fetcher(engram.clone(), state.fetch_request());
+    let mut snapshot: Option<Snapshot> = None;
+    let empty = Snapshot::default();
 
     loop {
-        // Fetch data outside of draw closure so we can use it for expansion
-        let memories = engram.list_memories_filtered(false).unwrap_or_default();
-        let events = engram.get_enriched_events(100, None, None, false).unwrap_or_default();
-
-        // Update counts
-        state.memories_count = memories.len();
-        state.events_count = events.len();
-
-        // Compute activity for chart
-        let activity = compute_hourly_activity(&events);
+        // Render the newest snapshot the fetcher has delivered
+        let mut updated = false;
+        for latest in snapshots.try_iter() {
+            snapshot = Some(latest);
+            updated = true;
+        }
+        let loading = snapshot.is_none();
+        let current = snapshot.as_ref().unwrap_or(&empty);
+        if updated {
+            state.apply(current);
+        }
+        let Snapshot { memories, events, activity, .. } = current;
 
         terminal.draw(|frame| {
             let area = frame.area();
 
+            // Reserve a status line for staged actions awaiting confirmation,
+            // or for what went wrong committing them
+            let (area, status_area) = if state.pending.is_empty() && state.status.is_none() {
+                (area, None)
+            } else {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(area);
+                (chunks[0], Some(chunks[1]))
+            };
+
             // Split screen: left (memories+events) and right (chart)
             let main_chunks = Layout::default()
                 .direction(Direction::Horizontal)
@@ -174,14 +501,14 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                 .iter()
                 .map(|m| {
                     let short_id = if m.id.len() > 8 { &m.id[..8] } else { &m.id };
-                    let content = truncate(&m.content, 60);
-                    ListItem::new(format!("[{}] taps:{:2} | {}", short_id, m.tap_count, content))
+                    let content = truncate(&m.content, MEMORY_WIDTH);
+                    let pin = if m.pinned { "[P]" } else { "   " };
+                    ListItem::new(format!("[{}]{} taps:{:2} | {}", short_id, pin, m.tap_count, content))
                 })
                 .collect();
 
             let events_items: Vec<ListItem> = events
                 .iter()
-                .take(50)
                 .map(|e| {
                     let time = format_timestamp(&e.timestamp);
                     let mem_id = e.memory_id.as_deref().unwrap_or("-");
@@ -192,24 +519,27 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                     let data_preview = if e.action == "TAP" {
                         String::new()
                     } else {
-                        truncate(&e.content, 40)
+                        truncate(&e.content, EVENT_PANEL_WIDTH)
                     };
 
-                    let text = format!("{} {:6} {} {}", time, e.action, short_id, data_preview);
-
-                    // Color code by memory_id
+                    // Color code by memory_id, with the action in its own color
                     let style = if mem_id != "-" {
                         Style::default().fg(color_for_memory_id(mem_id))
                     } else {
                         Style::default()
                     };
-                    ListItem::new(text).style(style)
+                    let action_style = action_color(&e.action).map_or(style, |c| Style::default().fg(c.into()));
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{} ", time), style),
+                        Span::styled(format!("{:6}", e.action), action_style),
+                        Span::styled(format!(" {} {}", short_id, data_preview), style),
+                    ]))
                 })
                 .collect();
 
             // Render memories panel
             let memories_title = if state.focused == Panel::Memories {
-                " Memories [*] "
+                " Memories [*] (p:promote f:forget t:tap) "
             } else {
                 " Memories "
             };
@@ -228,10 +558,18 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
             frame.render_stateful_widget(memories_list, left_chunks[0], &mut state.memories_state);
 
             // Render events panel
+            let filter_label = state.event_filter.action().unwrap_or("ALL");
+            let promoted_label = if state.include_promoted_taps { " +promoted taps" } else { "" };
             let events_title = if state.focused == Panel::Events {
-                " Events [*] (q:quit Tab:switch j/k:nav Enter:expand) "
+                format!(
+                    " Events [*] [{}{}] (q:quit Tab:switch j/k:nav Enter:expand a:filter P:promoted taps) ",
+                    filter_label, promoted_label
+                )
             } else {
-                " Events (q:quit Tab:switch j/k:nav Enter:expand) "
+                format!(
+                    " Events [{}{}] (q:quit Tab:switch j/k:nav Enter:expand a:filter P:promoted taps) ",
+                    filter_label, promoted_label
+                )
             };
             let events_block = Block::default()
                 .title(events_title)
@@ -277,6 +615,19 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                 })
                 .collect();
 
+            // Scale to the tallest visible bar; an idle window still needs a
+            // non-zero max
+            let chart_max = activity
+                .iter()
+                .map(|(_, adds, taps)| match state.chart_mode {
+                    ChartMode::Both => *adds.max(taps),
+                    ChartMode::AddOnly => *adds,
+                    ChartMode::TapOnly => *taps,
+                })
+                .max()
+                .unwrap_or(0)
+                .max(1);
+
             let chart_title = format!(
                 " Last 24h [{}] (c:cycle) ",
                 state.chart_mode.label()
@@ -290,13 +641,43 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                 .bar_width(3)
                 .bar_gap(1)
                 .group_gap(1)
-                .data(BarGroup::default().bars(&[])) // placeholder
-                .max(10);
+                .max(chart_max);
 
             // Render chart with groups
             let chart = bar_groups.iter().fold(chart, |c, g| c.data(g.clone()));
             frame.render_widget(chart, main_chunks[1]);
 
+            // Render staged actions awaiting confirmation
+            if let (Some(status_area), Some(message)) = (status_area, &state.status) {
+                let status = Paragraph::new(format!(" {}", message))
+                    .style(Style::default().fg(Color::White).bg(Color::Red));
+                frame.render_widget(status, status_area);
+            } else if let Some(status_area) = status_area {
+                let staged: Vec<String> = state
+                    .pending
+                    .iter()
+                    .map(|p| {
+                        let short_id = if p.memory_id.len() > 8 { &p.memory_id[..8] } else { &p.memory_id };
+                        format!("{} [{}] {}", p.kind.label(), short_id, p.preview)
+                    })
+                    .collect();
+                let status = Paragraph::new(format!(
+                    " Pending: {} (y:commit u:undo)",
+                    staged.join(", ")
+                ))
+                .style(Style::default().fg(Color::Black).bg(Color::Yellow));
+                frame.render_widget(status, status_area);
+            }
+
+            if loading {
+                let popup_area = centered_rect(30, 20, area);
+                frame.render_widget(Clear, popup_area);
+                let popup = Paragraph::new("Loading memories...")
+                    .alignment(Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL));
+                frame.render_widget(popup, popup_area);
+            }
+
             // Render expansion popup if active
             if let Some(ref expanded) = state.expanded {
                 let popup_area = centered_rect(80, 60, area);
@@ -313,8 +694,8 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
             }
         })?;
 
-        // Handle input (with timeout for refresh)
-        if event::poll(Duration::from_secs(1))? {
+        // Handle input; a short timeout keeps new snapshots showing promptly
+        if event::poll(INPUT_POLL)? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
                     // If popup is open, only handle Esc
@@ -325,12 +706,45 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                         continue;
                     }
 
+                    state.status = None;
                     match key.code {
                         KeyCode::Char('q') => break,
-                        KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+                        KeyCode::Char('j') | KeyCode::Down => {
+                            state.move_down();
+                            if state.load_more_events() {
+                                let _ = requests.send(state.fetch_request());
+                            }
+                        }
                         KeyCode::Char('k') | KeyCode::Up => state.move_up(),
                         KeyCode::Char('c') => state.chart_mode = state.chart_mode.next(),
+                        KeyCode::Char('a') => {
+                            state.event_filter = state.event_filter.next();
+                            state.events_limit = EVENTS_PAGE;
+                            let _ = requests.send(state.fetch_request());
+                        }
+                        KeyCode::Char('P') => {
+                            state.include_promoted_taps = !state.include_promoted_taps;
+                            state.events_limit = EVENTS_PAGE;
+                            let _ = requests.send(state.fetch_request());
+                        }
                         KeyCode::Tab => state.toggle_panel(),
+                        KeyCode::Char('p') | KeyCode::Char('f') | KeyCode::Char('t')
+                            if state.focused == Panel::Memories =>
+                        {
+                            let kind = match key.code {
+                                KeyCode::Char('p') => ActionKind::Promote,
+                                KeyCode::Char('f') => ActionKind::Forget,
+                                _ => ActionKind::Tap,
+                            };
+                            if let Some(m) = state.memories_state.selected().and_then(|i| memories.get(i)) {
+                                state.stage(kind, m);
+                            }
+                        }
+                        KeyCode::Char('u') => state.undo(),
+                        KeyCode::Char('y') => {
+                            state.commit(engram);
+                            let _ = requests.send(state.fetch_request());
+                        }
                         KeyCode::Enter => {
                             // Expand selected item
                             match state.focused {
@@ -340,11 +754,16 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                                             state.expanded = Some(ExpandedContent {
                                                 title: format!("Memory {}", &m.id[..8.min(m.id.len())]),
                                                 content: format!(
-                                                    "ID: {}\nTaps: {}\nCreated: {}\n\n{}",
+                                                    "ID: {}\nTaps: {}\nCreated: {}\nLooks like: {}\n\n{}",
                                                     m.id,
                                                     m.tap_count,
                                                     format_timestamp(&m.created_at),
-                                                    m.content
+                                                    m.content_kind,
+                                                    if m.content_kind == "code" {
+                                                        frame_code(&m.content)
+                                                    } else {
+                                                        sanitize_block(&m.content)
+                                                    }
                                                 ),
                                             });
                                         }
@@ -361,7 +780,7 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                                                     format_timestamp(&e.timestamp),
                                                     e.action,
                                                     mem_id,
-                                                    e.content
+                                                    sanitize_block(&e.content)
                                                 ),
                                             });
                                         }
@@ -371,6 +790,7 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
                         }
                         _ => {}
                     }
+                    state.remember_selection(memories, events);
                 }
             }
         }
@@ -379,6 +799,12 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result
     Ok(())
 }
 
+/// Pick the index for a tracked item: its new position if still present,
+/// otherwise the previous index clamped to the list
+fn resolve_index(found: Option<usize>, previous: Option<usize>, len: usize) -> usize {
+    found.unwrap_or_else(|| previous.unwrap_or(0).min(len.saturating_sub(1)))
+}
+
 /// Create a centered rectangle for popups
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -400,21 +826,6 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-fn format_timestamp(ts: &str) -> String {
-    // Parse RFC3339 datetime and extract time portion
-    chrono::DateTime::parse_from_rfc3339(ts)
-        .map(|dt| dt.format("%H:%M:%S").to_string())
-        .unwrap_or_else(|_| "Invalid".to_string())
-}
-
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
-
 /// Get a consistent color for a memory ID
 fn color_for_memory_id(id: &str) -> Color {
     const COLORS: [Color; 6] = [
@@ -474,3 +885,113 @@ fn compute_hourly_activity(events: &[EnrichedEvent]) -> Vec<(String, u64, u64)>
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engram::Config;
+
+    fn event(id: i64, timestamp: &str, action: &str, memory_id: &str) -> EnrichedEvent {
+        EnrichedEvent {
+            id,
+            timestamp: timestamp.to_string(),
+            action: action.to_string(),
+            memory_id: Some(memory_id.to_string()),
+            data: None,
+            content: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_collapse_reviews_merges_runs_in_the_same_second() {
+        let events = vec![
+            event(6, "2026-01-02 10:00:05.100", "REVIEW", "c"),
+            event(5, "2026-01-02 10:00:05.050", "REVIEW", "b"),
+            event(4, "2026-01-02 10:00:05.000", "REVIEW", "a"),
+            event(3, "2026-01-02 10:00:04.000", "REVIEW", "z"),
+            event(2, "2026-01-02 10:00:04.000", "TAP", "a"),
+            event(1, "2026-01-02 10:00:04.000", "REVIEW", "y"),
+        ];
+        let collapsed = collapse_reviews(events);
+
+        let rows: Vec<_> = collapsed.iter().map(|e| (e.id, e.memory_id.as_deref(), e.content.as_str())).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (6, None, "x3: c, b, a"),
+                // A different second starts a new run, and a lone REVIEW is left as it is
+                (3, Some("z"), ""),
+                (2, Some("a"), ""),
+                (1, Some("y"), ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commit_reports_failures_in_the_status_line() {
+        // A directory can't be written as CLAUDE.md, so the promote's sync fails
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config { db_path: ":memory:".into(), claude_md_path: Some(dir.path().to_path_buf()), ..Config::default() };
+        let engram = Engram::new(&config).unwrap();
+        let id = engram.add_memory("Worth keeping").unwrap();
+        let memory = engram.get_memory(&id).unwrap().unwrap();
+
+        let mut state = AppState::new();
+        state.stage(ActionKind::Tap, &memory);
+        state.commit(&engram);
+        assert_eq!(state.status, None);
+
+        state.stage(ActionKind::Promote, &memory);
+        state.commit(&engram);
+        let status = state.status.unwrap();
+        assert!(status.starts_with(&format!("PROMOTE [{}] promoted, but syncing CLAUDE.md failed", &id[..8])), "{}", status);
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn test_commit_reports_hook_failures() {
+        let config = Config {
+            db_path: ":memory:".into(),
+            on_forget: Some("echo nope >&2; exit 1".to_string()),
+            ..Config::default()
+        };
+        let engram = Engram::new(&config).unwrap();
+        let id = engram.add_memory("Drop me").unwrap();
+
+        let mut state = AppState::new();
+        state.stage(ActionKind::Forget, &engram.get_memory(&id).unwrap().unwrap());
+        state.commit(&engram);
+        let status = state.status.unwrap();
+        assert!(status.starts_with(&format!("hook failed for {}", id)) && status.contains("nope"), "{}", status);
+        // Drained, so they aren't reported again on exit
+        assert!(engram.take_hook_failures().is_empty());
+    }
+
+    #[test]
+    fn test_hidden_promoted_taps_dont_end_paging() {
+        let engram = Engram::new(&Config { db_path: ":memory:".into(), ..Config::default() }).unwrap();
+        let active = engram.add_memory("Still learning").unwrap();
+        let promoted = engram.add_memory("Graduated").unwrap();
+        engram.promote_memory(&promoted).unwrap();
+        engram.tap_memory(&active).unwrap();
+        // Newer than the visible tap, so dropping them after the LIMIT would empty the page
+        for _ in 0..5 {
+            engram.tap_memory(&promoted).unwrap();
+        }
+
+        let mut state = AppState::new();
+        state.focused = Panel::Events;
+        state.event_filter = EventFilter::Tap;
+        state.events_limit = 1;
+        state.apply(&Snapshot::fetch(&engram, state.fetch_request()));
+        assert_eq!(state.events_count, 1);
+        assert!(!state.events_exhausted());
+        assert!(state.load_more_events());
+
+        // Only the one visible tap exists, so the second page comes back short
+        state.apply(&Snapshot::fetch(&engram, state.fetch_request()));
+        assert_eq!(state.events_count, 1);
+        assert!(state.events_exhausted());
+        assert!(!state.load_more_events());
+    }
+}